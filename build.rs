@@ -0,0 +1,314 @@
+//! Embeds the metadata and a selection of the data files in the crate when the `embedded` feature
+//! is activated, see `src/corpus.rs`.
+//!
+//! The build script cannot use the crate itself, so it only reads the fields of the ron metadata
+//! that select the testcases, whatever their kind and the layout of the file. The layout of the
+//! data directory and the filter are the modules of the crate, included from their sources. The
+//! dependencies of the build script are only built with the `embedded` feature.
+
+#[cfg(feature = "embedded")]
+#[allow(dead_code)]
+#[path = "src/layout.rs"]
+mod layout;
+
+#[cfg(feature = "embedded")]
+#[allow(dead_code)]
+#[path = "src/filter.rs"]
+mod filter;
+
+#[cfg(feature = "embedded")]
+mod embedded {
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        env,
+        fmt::{self, Write},
+        fs,
+        path::Path,
+    };
+
+    use serde::{
+        de::{self, EnumAccess, IgnoredAny, MapAccess, VariantAccess, Visitor},
+        Deserialize, Deserializer,
+    };
+
+    use super::filter::{FilteredTest, TestFilter, DEFAULT_EMBED_FILTER, EMBED_FILTER_ENV_VAR};
+    use super::layout::{dir_for_version, schema_path, DATA_EXTENSIONS, METADATA_FILES};
+
+    /// The start of the files that git-lfs did not fetch
+    const LFS_POINTER_PREFIX: &[u8] = b"version https://git-lfs";
+
+    /// The fields of a testcase used to select it
+    #[derive(Deserialize)]
+    struct EmbeddedTestcase {
+        #[serde(rename = "tfhe_version_min")]
+        version: String,
+        #[serde(rename = "tfhe_module")]
+        module: String,
+        metadata: EmbeddedMetadata,
+        #[serde(default)]
+        tags: BTreeSet<String>,
+    }
+
+    impl EmbeddedTestcase {
+        fn filtered_test<'a>(&'a self, extension: &'a str) -> FilteredTest<'a> {
+            FilteredTest {
+                version: &self.version,
+                module: &self.module,
+                kind: &self.metadata.kind,
+                tags: &self.tags,
+                extension,
+            }
+        }
+
+        fn id(&self) -> (String, String, String) {
+            (
+                self.version.clone(),
+                self.module.clone(),
+                self.metadata.test_filename.clone(),
+            )
+        }
+    }
+
+    /// The kind of a testcase and the files it references, the other fields are ignored
+    struct EmbeddedMetadata {
+        kind: String,
+        test_filename: String,
+        /// The test filenames of the testcases this one references (eg: its `key_filename`)
+        auxiliaries: Vec<String>,
+    }
+
+    impl<'de> Deserialize<'de> for EmbeddedMetadata {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct MetadataVisitor;
+
+            impl<'de> Visitor<'de> for MetadataVisitor {
+                type Value = EmbeddedMetadata;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a kind of test")
+                }
+
+                fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+                    let (Identifier(kind), variant) = data.variant()?;
+                    let Filenames(mut filenames) = variant.newtype_variant()?;
+                    let test_filename = filenames
+                        .remove("test_filename")
+                        .ok_or_else(|| de::Error::missing_field("test_filename"))?;
+
+                    Ok(EmbeddedMetadata {
+                        kind,
+                        test_filename,
+                        auxiliaries: filenames.into_values().collect(),
+                    })
+                }
+            }
+
+            deserializer.deserialize_enum("TestMetadata", &[], MetadataVisitor)
+        }
+    }
+
+    /// The fields of the metadata of a testcase that reference files, named `<name>_filename`
+    struct Filenames(BTreeMap<String, String>);
+
+    impl<'de> Deserialize<'de> for Filenames {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct FilenamesVisitor;
+
+            impl<'de> Visitor<'de> for FilenamesVisitor {
+                type Value = Filenames;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("the metadata of a test")
+                }
+
+                fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Self::Value, M::Error> {
+                    let mut filenames = BTreeMap::new();
+                    while let Some(Identifier(field)) = map.next_key()? {
+                        if field.ends_with("_filename") {
+                            filenames.insert(field, map.next_value()?);
+                        } else {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+
+                    Ok(Filenames(filenames))
+                }
+            }
+
+            deserializer.deserialize_struct("Filenames", &[], FilenamesVisitor)
+        }
+    }
+
+    /// The name of a kind of test or of a field
+    struct Identifier(String);
+
+    impl<'de> Deserialize<'de> for Identifier {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct IdentifierVisitor;
+
+            impl<'de> Visitor<'de> for IdentifierVisitor {
+                type Value = Identifier;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("an identifier")
+                }
+
+                fn visit_str<E: de::Error>(self, name: &str) -> Result<Self::Value, E> {
+                    Ok(Identifier(name.to_string()))
+                }
+            }
+
+            deserializer.deserialize_identifier(IdentifierVisitor)
+        }
+    }
+
+    /// A metadata file written with a struct layout (schema version 2 and later)
+    #[derive(Deserialize)]
+    struct VersionedMetadata {
+        testcases: Vec<EmbeddedTestcase>,
+    }
+
+    /// Reads the testcases of a metadata file, written with the layout of any schema version
+    fn read_testcases(serialized: &str) -> Result<Vec<EmbeddedTestcase>, String> {
+        // The schema version 1 is a bare list of testcases, without header
+        let testcases = if ron::from_str::<Vec<IgnoredAny>>(serialized).is_ok() {
+            ron::from_str(serialized)
+        } else {
+            ron::from_str::<VersionedMetadata>(serialized).map(|metadata| metadata.testcases)
+        };
+
+        testcases.map_err(|e| e.to_string())
+    }
+
+    fn generate_embedded_corpus(data_dir: &Path, filter: &str) -> Result<String, String> {
+        let filter_terms = TestFilter::parse(filter)
+            .map_err(|e| format!("Invalid {} variable: {}", EMBED_FILTER_ENV_VAR, e))?;
+
+        let mut testcases = Vec::new();
+        let mut metadata = Vec::new();
+        for (module, filename) in METADATA_FILES {
+            let path = data_dir.join(filename);
+            let serialized = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+            testcases.extend(
+                read_testcases(&serialized)
+                    .map_err(|e| format!("Invalid {}: {}", path.display(), e))?,
+            );
+            metadata.push((module, path));
+        }
+
+        // The selected testcases, in each format, with the auxiliaries they need
+        let mut selected = BTreeSet::new();
+        for testcase in &testcases {
+            for extension in DATA_EXTENSIONS {
+                if filter_terms.selects_test(&testcase.filtered_test(extension)) {
+                    selected.insert((testcase.id(), extension));
+                    for auxiliary in &testcase.metadata.auxiliaries {
+                        let (version, module, _) = testcase.id();
+                        selected.insert(((version, module, auxiliary.clone()), extension));
+                    }
+                }
+            }
+        }
+
+        let mut generated = String::new();
+        let mut push = |line: String| writeln!(generated, "{}", line).unwrap();
+
+        push(format!("pub(super) const FILTER: &str = {:?};", filter));
+
+        push("pub(super) static METADATA: &[(&str, &str)] = &[".to_string());
+        for (module, path) in metadata {
+            push(format!(
+                "({:?}, include_str!({:?})),",
+                module,
+                path.display().to_string()
+            ));
+        }
+        push("];".to_string());
+
+        push("pub(super) static FILES: &[(&str, &str, &str, &str, &[u8])] = &[".to_string());
+        let ids: BTreeSet<_> = testcases.iter().map(EmbeddedTestcase::id).collect();
+        for (id, extension) in &selected {
+            let (version, module, test_filename) = id;
+            if !ids.contains(id) {
+                return Err(format!(
+                    "Testcase {}/{}/{} is referenced but not declared",
+                    version, module, test_filename
+                ));
+            }
+            let path = dir_for_version(data_dir, version)
+                .join(module)
+                .join(format!("{}.{}", test_filename, extension));
+
+            let data =
+                fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            if data.starts_with(LFS_POINTER_PREFIX) {
+                return Err(format!(
+                "{} is a git-lfs pointer, the data should be fetched with `git lfs pull` before \
+                they are embedded",
+                path.display()
+            ));
+            }
+
+            push(format!(
+                "({:?}, {:?}, {:?}, {:?}, include_bytes!({:?})),",
+                version,
+                module,
+                test_filename,
+                extension,
+                path.display().to_string()
+            ));
+        }
+        push("];".to_string());
+
+        push("pub(super) static SCHEMAS: &[(&str, &str, &str, &str)] = &[".to_string());
+        let selected_ids: BTreeSet<_> = selected.iter().map(|(id, _)| id).collect();
+        for testcase in &testcases {
+            let path = schema_path(
+                dir_for_version(data_dir, &testcase.version).join(&testcase.module),
+                &testcase.metadata.test_filename,
+            );
+
+            if selected_ids.contains(&testcase.id()) && path.is_file() {
+                push(format!(
+                    "({:?}, {:?}, {:?}, include_str!({:?})),",
+                    testcase.version,
+                    testcase.module,
+                    testcase.metadata.test_filename,
+                    path.display().to_string()
+                ));
+            }
+        }
+        push("];".to_string());
+
+        Ok(generated)
+    }
+
+    pub(super) fn embed_corpus() {
+        println!("cargo:rerun-if-changed=src/layout.rs");
+        println!("cargo:rerun-if-changed=src/filter.rs");
+
+        let data_dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("data");
+        println!("cargo:rerun-if-changed={}", data_dir.display());
+        println!("cargo:rerun-if-env-changed={}", EMBED_FILTER_ENV_VAR);
+
+        let filter = match env::var(EMBED_FILTER_ENV_VAR) {
+            Ok(filter) => filter,
+            Err(env::VarError::NotPresent) => DEFAULT_EMBED_FILTER.to_string(),
+            Err(e) => panic!("Invalid {} variable: {}", EMBED_FILTER_ENV_VAR, e),
+        };
+        let generated =
+            generate_embedded_corpus(&data_dir, &filter).unwrap_or_else(|e| panic!("{}", e));
+
+        let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("embedded_corpus.rs");
+        fs::write(out_path, generated).unwrap();
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "embedded")]
+    embedded::embed_corpus();
+}
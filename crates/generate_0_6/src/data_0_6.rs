@@ -0,0 +1,762 @@
+use std::borrow::Cow;
+
+use tfhe_0_6::{
+    boolean::engine::BooleanEngine,
+    core_crypto::commons::{
+        generators::DeterministicSeeder,
+        math::random::{ActivatedRandomGenerator, Seed},
+    },
+    prelude::FheEncrypt,
+    set_server_key,
+    shortint::{
+        self,
+        engine::ShortintEngine,
+        parameters::{
+            DecompositionBaseLog, DecompositionLevelCount, DynamicDistribution, GlweDimension,
+            LweDimension, PolynomialSize, StandardDev, PARAM_MESSAGE_1_CARRY_1_PBS_KS,
+        },
+        CarryModulus, CiphertextModulus, ClassicPBSParameters, EncryptionKeyChoice, MaxNoiseLevel,
+        MessageModulus, PBSParameters,
+    },
+    ClientKey, CompactFheBool, CompactFheBoolList, CompactFheInt8, CompactFheInt8List,
+    CompactFheUint8, CompactFheUint8List, CompactPublicKey, CompressedCompactPublicKey,
+    CompressedFheBool, CompressedFheInt8, CompressedFheUint8, CompressedPublicKey,
+    CompressedServerKey, ConfigBuilder, FheBool, FheInt8, FheUint8, PublicKey, ServerKey,
+};
+use tfhe_versionable::Versionize;
+
+use tfhe_backward_compat_data::{
+    generate::{
+        GenerationUnit, TestcaseDefinition, TfhersVersion, VersionedData, VALID_TEST_PARAMS,
+    },
+    ClearInteger, ClearValue, HlBoolCiphertextListTest, HlBoolCiphertextTest, HlCiphertextListTest,
+    HlCiphertextTest, HlClientKeyTest, HlPublicKeyTest, HlServerKeyTest,
+    HlSignedCiphertextListTest, HlSignedCiphertextTest, ShortintCiphertextTest,
+    ShortintClientKeyTest, StoredType, TestMetadata, TestParameterSet, LARGE_TAG, SLOW_TAG,
+};
+
+/// Conversion of the parameters described in the metadata into the tfhe-rs parameters of this
+/// version
+trait ConvertParams<TfheParams> {
+    fn convert(self) -> TfheParams;
+}
+
+impl ConvertParams<ClassicPBSParameters> for TestParameterSet {
+    fn convert(self) -> ClassicPBSParameters {
+        ClassicPBSParameters {
+            lwe_dimension: LweDimension(self.lwe_dimension),
+            glwe_dimension: GlweDimension(self.glwe_dimension),
+            polynomial_size: PolynomialSize(self.polynomial_size),
+            lwe_noise_distribution: DynamicDistribution::new_gaussian_from_std_dev(StandardDev(
+                self.lwe_noise_gaussian_stddev,
+            )),
+            glwe_noise_distribution: DynamicDistribution::new_gaussian_from_std_dev(StandardDev(
+                self.glwe_noise_gaussian_stddev,
+            )),
+            pbs_base_log: DecompositionBaseLog(self.pbs_base_log),
+            pbs_level: DecompositionLevelCount(self.pbs_level),
+            ks_base_log: DecompositionBaseLog(self.ks_base_log),
+            ks_level: DecompositionLevelCount(self.ks_level),
+            message_modulus: MessageModulus(self.message_modulus),
+            carry_modulus: CarryModulus(self.carry_modulus),
+            max_noise_level: MaxNoiseLevel::new(self.max_noise_level),
+            log2_p_fail: self.log2_p_fail,
+            ciphertext_modulus: CiphertextModulus::try_new(self.ciphertext_modulus).unwrap(),
+            encryption_key_choice: {
+                match &*self.encryption_key_choice {
+                    "big" => EncryptionKeyChoice::Big,
+                    "small" => EncryptionKeyChoice::Small,
+                    _ => panic!("Invalid encryption key choice"),
+                }
+            },
+        }
+    }
+}
+
+impl ConvertParams<TestParameterSet> for ClassicPBSParameters {
+    fn convert(self) -> TestParameterSet {
+        let gaussian_stddev = |distribution: DynamicDistribution<u64>| match distribution {
+            DynamicDistribution::Gaussian(gaussian) => gaussian.std,
+            _ => panic!("Only the gaussian noise distribution can be described in the metadata"),
+        };
+
+        TestParameterSet {
+            lwe_dimension: self.lwe_dimension.0,
+            glwe_dimension: self.glwe_dimension.0,
+            polynomial_size: self.polynomial_size.0,
+            lwe_noise_gaussian_stddev: gaussian_stddev(self.lwe_noise_distribution),
+            glwe_noise_gaussian_stddev: gaussian_stddev(self.glwe_noise_distribution),
+            pbs_base_log: self.pbs_base_log.0,
+            pbs_level: self.pbs_level.0,
+            ks_base_log: self.ks_base_log.0,
+            ks_level: self.ks_level.0,
+            message_modulus: self.message_modulus.0,
+            carry_modulus: self.carry_modulus.0,
+            max_noise_level: self.max_noise_level.get(),
+            log2_p_fail: self.log2_p_fail,
+            ciphertext_modulus: if self.ciphertext_modulus.is_native_modulus() {
+                (u64::MAX as u128) + 1
+            } else {
+                self.ciphertext_modulus.get_custom_modulus()
+            },
+            encryption_key_choice: match self.encryption_key_choice {
+                EncryptionKeyChoice::Big => Cow::Borrowed("big"),
+                EncryptionKeyChoice::Small => Cow::Borrowed("small"),
+            },
+        }
+    }
+}
+
+impl ConvertParams<PBSParameters> for TestParameterSet {
+    fn convert(self) -> PBSParameters {
+        let classic_pbs: ClassicPBSParameters = self.convert();
+        classic_pbs.into()
+    }
+}
+
+/// Versionizes the tfhe-rs object so that it can be stored in all the formats, and records its
+/// type and the function used to build it
+fn versioned<Data: Versionize>(msg: &Data, construction: &str) -> VersionedData {
+    VersionedData::new(&msg.versionize()).with_stored_type(StoredType::new::<Data>(construction))
+}
+
+/// Same as [`versioned`], for the integer ciphertexts that encrypt the `clear_values` of their
+/// metadata as `Clear` integers. The width of the encrypted integers is the one of `Clear`.
+///
+/// Panics if the clear values do not have the width and signedness of `Clear`.
+fn versioned_integer<Clear: ClearInteger, Data: Versionize>(
+    msg: &Data,
+    clear_values: &[ClearValue],
+    construction: &str,
+) -> VersionedData {
+    for clear in clear_values {
+        if let Err(e) = clear.try_to_clear::<Clear>() {
+            panic!("Invalid clear value for {}: {}", construction, e);
+        }
+    }
+
+    VersionedData::new(&msg.versionize())
+        .with_stored_type(StoredType::new::<Data>(construction).with_num_bits(Clear::NUM_BITS))
+}
+
+const SHORTINT_CLIENTKEY_TEST: ShortintClientKeyTest = ShortintClientKeyTest {
+    test_filename: Cow::Borrowed("client_key"),
+    parameters: VALID_TEST_PARAMS,
+};
+const SHORTINT_CT1_TEST: ShortintCiphertextTest = ShortintCiphertextTest {
+    test_filename: Cow::Borrowed("ct1"),
+    key_filename: Cow::Borrowed("client_key"),
+    clear_value: 0,
+};
+const SHORTINT_CT2_TEST: ShortintCiphertextTest = ShortintCiphertextTest {
+    test_filename: Cow::Borrowed("ct2"),
+    key_filename: Cow::Borrowed("client_key"),
+    clear_value: 3,
+};
+
+const HL_CLIENTKEY_TEST: HlClientKeyTest = HlClientKeyTest {
+    test_filename: Cow::Borrowed("client_key"),
+    parameters: VALID_TEST_PARAMS,
+};
+
+/// Parameters used to generate a small public key. The lwe dimension is very small so that the
+/// public key, that is built with the small lwe key, stays small.
+/// WARNING: these parameters are completely insecure
+fn insecure_small_pk_params() -> ClassicPBSParameters {
+    let mut params = PARAM_MESSAGE_1_CARRY_1_PBS_KS;
+    params.lwe_dimension = LweDimension(10);
+    params
+}
+
+// We use a client key with specific parameters for the pubkey since it can be very large. Its
+// metadata are built from the tfhe-rs parameters, so that they always describe the stored key.
+fn hl_clientkey_for_pubkey_test() -> HlClientKeyTest {
+    HlClientKeyTest {
+        test_filename: Cow::Borrowed("client_key_for_pubkey"),
+        parameters: insecure_small_pk_params().convert(),
+    }
+}
+
+const HL_SERVERKEY_TEST: HlServerKeyTest = HlServerKeyTest {
+    test_filename: Cow::Borrowed("server_key"),
+    client_key_filename: Cow::Borrowed("client_key"),
+    compressed: false,
+};
+
+const HL_COMPRESSED_SERVERKEY_TEST: HlServerKeyTest = HlServerKeyTest {
+    test_filename: Cow::Borrowed("compressed_server_key"),
+    client_key_filename: Cow::Borrowed("client_key"),
+    compressed: true,
+};
+
+const HL_PUBKEY_TEST: HlPublicKeyTest = HlPublicKeyTest {
+    test_filename: Cow::Borrowed("public_key"),
+    client_key_filename: Cow::Borrowed("client_key_for_pubkey"),
+    compressed: false,
+    compact: false,
+};
+
+const HL_COMPRESSED_PUBKEY_TEST: HlPublicKeyTest = HlPublicKeyTest {
+    test_filename: Cow::Borrowed("compressed_public_key"),
+    client_key_filename: Cow::Borrowed("client_key"),
+    compressed: true,
+    compact: false,
+};
+
+const HL_COMPACT_PUBKEY_TEST: HlPublicKeyTest = HlPublicKeyTest {
+    test_filename: Cow::Borrowed("compact_public_key"),
+    client_key_filename: Cow::Borrowed("client_key"),
+    compressed: false,
+    compact: true,
+};
+
+const HL_COMPRESSED_COMPACT_PUBKEY_TEST: HlPublicKeyTest = HlPublicKeyTest {
+    test_filename: Cow::Borrowed("compressed_compact_public_key"),
+    client_key_filename: Cow::Borrowed("client_key"),
+    compressed: true,
+    compact: true,
+};
+
+const HL_CT1_TEST: HlCiphertextTest = HlCiphertextTest {
+    test_filename: Cow::Borrowed("ct1"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: false,
+    compact: false,
+    clear_value: ClearValue::unsigned(0, 8),
+};
+
+const HL_CT2_TEST: HlCiphertextTest = HlCiphertextTest {
+    test_filename: Cow::Borrowed("ct2"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: false,
+    compact: false,
+    clear_value: ClearValue::unsigned(255, 8),
+};
+
+const HL_COMPACT_CT_TEST: HlCiphertextTest = HlCiphertextTest {
+    test_filename: Cow::Borrowed("ct_compact"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: false,
+    compact: true,
+    clear_value: ClearValue::unsigned(255, 8),
+};
+
+const HL_COMPRESSED_SEEDED_CT_TEST: HlCiphertextTest = HlCiphertextTest {
+    test_filename: Cow::Borrowed("ct_compressed_seeded"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: true,
+    compact: false,
+    clear_value: ClearValue::unsigned(255, 8),
+};
+
+const HL_COMPRESSED_CT_MODSWITCHED_TEST: HlCiphertextTest = HlCiphertextTest {
+    test_filename: Cow::Borrowed("ct_compressed_modswitched"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: true,
+    compact: false,
+    clear_value: ClearValue::unsigned(255, 8),
+};
+
+const HL_CT_LIST_TEST: HlCiphertextListTest = HlCiphertextListTest {
+    test_filename: Cow::Borrowed("ct_list"),
+    key_filename: Cow::Borrowed("client_key"),
+    clear_values: Cow::Borrowed(&[ClearValue::unsigned(0, 8), ClearValue::unsigned(255, 8)]),
+};
+
+const HL_SIGNED_CT1_TEST: HlSignedCiphertextTest = HlSignedCiphertextTest {
+    test_filename: Cow::Borrowed("ct1_signed"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: false,
+    compact: false,
+    clear_value: ClearValue::signed(0, 8),
+};
+
+const HL_SIGNED_CT2_TEST: HlSignedCiphertextTest = HlSignedCiphertextTest {
+    test_filename: Cow::Borrowed("ct2_signed"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: false,
+    compact: false,
+    clear_value: ClearValue::signed(-127, 8),
+};
+
+const HL_SIGNED_COMPACT_CT_TEST: HlSignedCiphertextTest = HlSignedCiphertextTest {
+    test_filename: Cow::Borrowed("ct_compact_signed"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: false,
+    compact: true,
+    clear_value: ClearValue::signed(-1, 8),
+};
+
+const HL_SIGNED_CT_LIST_TEST: HlSignedCiphertextListTest = HlSignedCiphertextListTest {
+    test_filename: Cow::Borrowed("ct_list_signed"),
+    key_filename: Cow::Borrowed("client_key"),
+    clear_values: Cow::Borrowed(&[
+        ClearValue::signed(-127, 8),
+        ClearValue::signed(0, 8),
+        ClearValue::signed(127, 8),
+    ]),
+};
+
+const HL_SIGNED_COMPRESSED_SEEDED_CT_TEST: HlSignedCiphertextTest = HlSignedCiphertextTest {
+    test_filename: Cow::Borrowed("ct_compressed_seeded_signed"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: true,
+    compact: false,
+    clear_value: ClearValue::signed(-1, 8),
+};
+
+const HL_SIGNED_COMPRESSED_CT_MODSWITCHED_TEST: HlSignedCiphertextTest = HlSignedCiphertextTest {
+    test_filename: Cow::Borrowed("ct_compressed_modswitched_signed"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: true,
+    compact: false,
+    clear_value: ClearValue::signed(-1, 8),
+};
+
+const HL_BOOL1_TEST: HlBoolCiphertextTest = HlBoolCiphertextTest {
+    test_filename: Cow::Borrowed("bool1"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: false,
+    compact: false,
+    clear_value: true,
+};
+
+const HL_BOOL2_TEST: HlBoolCiphertextTest = HlBoolCiphertextTest {
+    test_filename: Cow::Borrowed("bool2"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: false,
+    compact: false,
+    clear_value: false,
+};
+
+const HL_COMPACT_BOOL_TEST: HlBoolCiphertextTest = HlBoolCiphertextTest {
+    test_filename: Cow::Borrowed("compact_bool"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: false,
+    compact: true,
+    clear_value: true,
+};
+
+const HL_COMPRESSED_BOOL_SEEDED_TEST: HlBoolCiphertextTest = HlBoolCiphertextTest {
+    test_filename: Cow::Borrowed("compressed_seeded_bool"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: true,
+    compact: false,
+    clear_value: true,
+};
+
+const HL_COMPRESSED_BOOL_MODSWITCHED_TEST: HlBoolCiphertextTest = HlBoolCiphertextTest {
+    test_filename: Cow::Borrowed("compressed_modswitched_bool"),
+    key_filename: Cow::Borrowed("client_key"),
+    compressed: true,
+    compact: false,
+    clear_value: true,
+};
+
+const HL_BOOL_LIST_TEST: HlBoolCiphertextListTest = HlBoolCiphertextListTest {
+    test_filename: Cow::Borrowed("bool_list"),
+    key_filename: Cow::Borrowed("client_key"),
+    clear_values: Cow::Borrowed(&[true, false, true]),
+};
+
+pub struct V0_6;
+
+impl V0_6 {
+    /// Regenerates the shortint client key of the testcase `key_filename`, using its seed
+    fn shortint_client_key(unit: &GenerationUnit, key_filename: &str) -> shortint::ClientKey {
+        assert_eq!(key_filename, SHORTINT_CLIENTKEY_TEST.test_filename);
+
+        unit.regenerate(key_filename, || {
+            let params: PBSParameters = SHORTINT_CLIENTKEY_TEST.parameters.convert();
+            shortint::ClientKey::new(params)
+        })
+    }
+
+    /// Regenerates the HL client key of the testcase `key_filename`, using its seed
+    fn hl_client_key(unit: &GenerationUnit, key_filename: &str) -> ClientKey {
+        let key_test = [HL_CLIENTKEY_TEST, hl_clientkey_for_pubkey_test()]
+            .into_iter()
+            .find(|test| test.test_filename == key_filename)
+            .unwrap_or_else(|| panic!("Unknown HL client key {}", key_filename));
+
+        unit.regenerate(key_filename, || {
+            let params: ClassicPBSParameters = key_test.parameters.convert();
+            ClientKey::generate(ConfigBuilder::with_custom_parameters(params, None).build())
+        })
+    }
+
+    /// Regenerates the HL server key of the [`HL_SERVERKEY_TEST`] testcase, using its seed
+    fn hl_server_key(unit: &GenerationUnit) -> ServerKey {
+        let hl_client_key = Self::hl_client_key(unit, &HL_SERVERKEY_TEST.client_key_filename);
+
+        unit.regenerate(&HL_SERVERKEY_TEST.test_filename, || {
+            ServerKey::new(&hl_client_key)
+        })
+    }
+
+    /// Regenerates the compact public key of the [`HL_COMPACT_PUBKEY_TEST`] testcase, using its
+    /// seed
+    fn hl_compact_public_key(unit: &GenerationUnit) -> CompactPublicKey {
+        let hl_client_key = Self::hl_client_key(unit, &HL_COMPACT_PUBKEY_TEST.client_key_filename);
+
+        unit.regenerate(&HL_COMPACT_PUBKEY_TEST.test_filename, || {
+            CompactPublicKey::new(&hl_client_key)
+        })
+    }
+}
+
+impl TfhersVersion for V0_6 {
+    const VERSION_NUMBER: &'static str = "0.6";
+    // The 0.6 data were generated sequentially from a single seed, before each testcase had its
+    // own seed, so generating them again would modify all of them
+    const FROZEN: bool = true;
+
+    fn seed_prng(seed: u128) {
+        let mut seeder = DeterministicSeeder::<ActivatedRandomGenerator>::new(Seed(seed));
+        let shortint_engine = ShortintEngine::new_from_seeder(&mut seeder);
+        ShortintEngine::with_thread_local_mut(|local_engine| {
+            let _ = std::mem::replace(local_engine, shortint_engine);
+        });
+
+        let boolean_engine = BooleanEngine::new_from_seeder(&mut seeder);
+        BooleanEngine::replace_thread_local(boolean_engine);
+    }
+
+    fn testcases() -> Vec<TestcaseDefinition> {
+        vec![
+            // Shortint
+            TestcaseDefinition::new(
+                TestMetadata::ShortintClientKey(SHORTINT_CLIENTKEY_TEST),
+                |unit| {
+                    versioned(
+                        &Self::shortint_client_key(unit, &SHORTINT_CLIENTKEY_TEST.test_filename),
+                        "shortint::ClientKey::new",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(
+                TestMetadata::ShortintCiphertext(SHORTINT_CT1_TEST),
+                |unit| {
+                    let shortint_client_key =
+                        Self::shortint_client_key(unit, &SHORTINT_CT1_TEST.key_filename);
+                    versioned(
+                        &shortint_client_key.encrypt(SHORTINT_CT1_TEST.clear_value),
+                        "shortint::ClientKey::encrypt",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(
+                TestMetadata::ShortintCiphertext(SHORTINT_CT2_TEST),
+                |unit| {
+                    let shortint_client_key =
+                        Self::shortint_client_key(unit, &SHORTINT_CT2_TEST.key_filename);
+                    versioned(
+                        &shortint_client_key.encrypt(SHORTINT_CT2_TEST.clear_value),
+                        "shortint::ClientKey::encrypt",
+                    )
+                },
+            ),
+            // High level api: keys
+            TestcaseDefinition::new(TestMetadata::HlClientKey(HL_CLIENTKEY_TEST), |unit| {
+                versioned(
+                    &Self::hl_client_key(unit, &HL_CLIENTKEY_TEST.test_filename),
+                    "ClientKey::generate",
+                )
+            }),
+            TestcaseDefinition::new(
+                TestMetadata::HlClientKey(hl_clientkey_for_pubkey_test()),
+                |unit| {
+                    versioned(
+                        &Self::hl_client_key(unit, &hl_clientkey_for_pubkey_test().test_filename),
+                        "ClientKey::generate",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(TestMetadata::HlServerKey(HL_SERVERKEY_TEST), |unit| {
+                versioned(&Self::hl_server_key(unit), "ServerKey::new")
+            })
+            .with_tags(&[SLOW_TAG, LARGE_TAG]),
+            TestcaseDefinition::new(TestMetadata::HlPublicKey(HL_PUBKEY_TEST), |unit| {
+                let hl_client_key = Self::hl_client_key(unit, &HL_PUBKEY_TEST.client_key_filename);
+                versioned(&PublicKey::new(&hl_client_key), "PublicKey::new")
+            }),
+            TestcaseDefinition::new(
+                TestMetadata::HlPublicKey(HL_COMPRESSED_PUBKEY_TEST),
+                |unit| {
+                    let hl_client_key =
+                        Self::hl_client_key(unit, &HL_COMPRESSED_PUBKEY_TEST.client_key_filename);
+                    versioned(
+                        &CompressedPublicKey::new(&hl_client_key),
+                        "CompressedPublicKey::new",
+                    )
+                },
+            )
+            .with_tags(&[SLOW_TAG, LARGE_TAG]),
+            TestcaseDefinition::new(TestMetadata::HlPublicKey(HL_COMPACT_PUBKEY_TEST), |unit| {
+                versioned(&Self::hl_compact_public_key(unit), "CompactPublicKey::new")
+            }),
+            TestcaseDefinition::new(
+                TestMetadata::HlPublicKey(HL_COMPRESSED_COMPACT_PUBKEY_TEST),
+                |unit| {
+                    let hl_client_key = Self::hl_client_key(
+                        unit,
+                        &HL_COMPRESSED_COMPACT_PUBKEY_TEST.client_key_filename,
+                    );
+                    versioned(
+                        &CompressedCompactPublicKey::new(&hl_client_key),
+                        "CompressedCompactPublicKey::new",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(
+                TestMetadata::HlServerKey(HL_COMPRESSED_SERVERKEY_TEST),
+                |unit| {
+                    let hl_client_key = Self::hl_client_key(
+                        unit,
+                        &HL_COMPRESSED_SERVERKEY_TEST.client_key_filename,
+                    );
+                    versioned(
+                        &CompressedServerKey::new(&hl_client_key),
+                        "CompressedServerKey::new",
+                    )
+                },
+            )
+            .with_tags(&[SLOW_TAG, LARGE_TAG]),
+            // High level api: unsigned ciphertexts
+            TestcaseDefinition::new(TestMetadata::HlCiphertext(HL_CT1_TEST), |unit| {
+                let hl_client_key = Self::hl_client_key(unit, &HL_CT1_TEST.key_filename);
+                let clear = HL_CT1_TEST.clear_value.to_clear::<u8>();
+                versioned_integer::<u8, _>(
+                    &FheUint8::encrypt(clear, &hl_client_key),
+                    &[HL_CT1_TEST.clear_value],
+                    "FheUint8::encrypt",
+                )
+            }),
+            TestcaseDefinition::new(TestMetadata::HlCiphertext(HL_CT2_TEST), |unit| {
+                let hl_client_key = Self::hl_client_key(unit, &HL_CT2_TEST.key_filename);
+                let clear = HL_CT2_TEST.clear_value.to_clear::<u8>();
+                versioned_integer::<u8, _>(
+                    &FheUint8::encrypt(clear, &hl_client_key),
+                    &[HL_CT2_TEST.clear_value],
+                    "FheUint8::encrypt",
+                )
+            }),
+            // Compressed ciphertexts are generated using the seeded (default) method, and using
+            // the modulus switched method
+            TestcaseDefinition::new(
+                TestMetadata::HlCiphertext(HL_COMPRESSED_SEEDED_CT_TEST),
+                |unit| {
+                    let hl_client_key =
+                        Self::hl_client_key(unit, &HL_COMPRESSED_SEEDED_CT_TEST.key_filename);
+                    let clear = HL_COMPRESSED_SEEDED_CT_TEST.clear_value.to_clear::<u8>();
+                    versioned_integer::<u8, _>(
+                        &CompressedFheUint8::encrypt(clear, &hl_client_key),
+                        &[HL_COMPRESSED_SEEDED_CT_TEST.clear_value],
+                        "CompressedFheUint8::encrypt",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(
+                TestMetadata::HlCiphertext(HL_COMPRESSED_CT_MODSWITCHED_TEST),
+                |unit| {
+                    let hl_client_key =
+                        Self::hl_client_key(unit, &HL_COMPRESSED_CT_MODSWITCHED_TEST.key_filename);
+                    set_server_key(Self::hl_server_key(unit));
+
+                    let clear = HL_COMPRESSED_CT_MODSWITCHED_TEST
+                        .clear_value
+                        .to_clear::<u8>();
+                    versioned_integer::<u8, _>(
+                        &FheUint8::encrypt(clear, &hl_client_key).compress(),
+                        &[HL_COMPRESSED_CT_MODSWITCHED_TEST.clear_value],
+                        "FheUint8::compress",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(TestMetadata::HlCiphertext(HL_COMPACT_CT_TEST), |unit| {
+                let compact_pub_key = Self::hl_compact_public_key(unit);
+                let clear = HL_COMPACT_CT_TEST.clear_value.to_clear::<u8>();
+                versioned_integer::<u8, _>(
+                    &CompactFheUint8::encrypt(clear, &compact_pub_key),
+                    &[HL_COMPACT_CT_TEST.clear_value],
+                    "CompactFheUint8::encrypt",
+                )
+            }),
+            TestcaseDefinition::new(TestMetadata::HlCiphertextList(HL_CT_LIST_TEST), |unit| {
+                let compact_pub_key = Self::hl_compact_public_key(unit);
+                let clear_values: Vec<u8> = HL_CT_LIST_TEST
+                    .clear_values
+                    .iter()
+                    .map(ClearValue::to_clear)
+                    .collect();
+                versioned_integer::<u8, _>(
+                    &CompactFheUint8List::encrypt(&clear_values, &compact_pub_key),
+                    &HL_CT_LIST_TEST.clear_values,
+                    "CompactFheUint8List::encrypt",
+                )
+            }),
+            // High level api: signed ciphertexts
+            TestcaseDefinition::new(
+                TestMetadata::HlSignedCiphertext(HL_SIGNED_CT1_TEST),
+                |unit| {
+                    let hl_client_key = Self::hl_client_key(unit, &HL_SIGNED_CT1_TEST.key_filename);
+                    let clear = HL_SIGNED_CT1_TEST.clear_value.to_clear::<i8>();
+                    versioned_integer::<i8, _>(
+                        &FheInt8::encrypt(clear, &hl_client_key),
+                        &[HL_SIGNED_CT1_TEST.clear_value],
+                        "FheInt8::encrypt",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(
+                TestMetadata::HlSignedCiphertext(HL_SIGNED_CT2_TEST),
+                |unit| {
+                    let hl_client_key = Self::hl_client_key(unit, &HL_SIGNED_CT2_TEST.key_filename);
+                    let clear = HL_SIGNED_CT2_TEST.clear_value.to_clear::<i8>();
+                    versioned_integer::<i8, _>(
+                        &FheInt8::encrypt(clear, &hl_client_key),
+                        &[HL_SIGNED_CT2_TEST.clear_value],
+                        "FheInt8::encrypt",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(
+                TestMetadata::HlSignedCiphertext(HL_SIGNED_COMPRESSED_SEEDED_CT_TEST),
+                |unit| {
+                    let hl_client_key = Self::hl_client_key(
+                        unit,
+                        &HL_SIGNED_COMPRESSED_SEEDED_CT_TEST.key_filename,
+                    );
+                    let clear = HL_SIGNED_COMPRESSED_SEEDED_CT_TEST
+                        .clear_value
+                        .to_clear::<i8>();
+                    versioned_integer::<i8, _>(
+                        &CompressedFheInt8::encrypt(clear, &hl_client_key),
+                        &[HL_SIGNED_COMPRESSED_SEEDED_CT_TEST.clear_value],
+                        "CompressedFheInt8::encrypt",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(
+                TestMetadata::HlSignedCiphertext(HL_SIGNED_COMPRESSED_CT_MODSWITCHED_TEST),
+                |unit| {
+                    let hl_client_key = Self::hl_client_key(
+                        unit,
+                        &HL_SIGNED_COMPRESSED_CT_MODSWITCHED_TEST.key_filename,
+                    );
+                    set_server_key(Self::hl_server_key(unit));
+
+                    let clear = HL_SIGNED_COMPRESSED_CT_MODSWITCHED_TEST
+                        .clear_value
+                        .to_clear::<i8>();
+                    versioned_integer::<i8, _>(
+                        &FheInt8::encrypt(clear, &hl_client_key).compress(),
+                        &[HL_SIGNED_COMPRESSED_CT_MODSWITCHED_TEST.clear_value],
+                        "FheInt8::compress",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(
+                TestMetadata::HlSignedCiphertext(HL_SIGNED_COMPACT_CT_TEST),
+                |unit| {
+                    let compact_pub_key = Self::hl_compact_public_key(unit);
+                    let clear = HL_SIGNED_COMPACT_CT_TEST.clear_value.to_clear::<i8>();
+                    versioned_integer::<i8, _>(
+                        &CompactFheInt8::encrypt(clear, &compact_pub_key),
+                        &[HL_SIGNED_COMPACT_CT_TEST.clear_value],
+                        "CompactFheInt8::encrypt",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(
+                TestMetadata::HlSignedCiphertextList(HL_SIGNED_CT_LIST_TEST),
+                |unit| {
+                    let compact_pub_key = Self::hl_compact_public_key(unit);
+                    let clear_values: Vec<i8> = HL_SIGNED_CT_LIST_TEST
+                        .clear_values
+                        .iter()
+                        .map(ClearValue::to_clear)
+                        .collect();
+                    versioned_integer::<i8, _>(
+                        &CompactFheInt8List::encrypt(&clear_values, &compact_pub_key),
+                        &HL_SIGNED_CT_LIST_TEST.clear_values,
+                        "CompactFheInt8List::encrypt",
+                    )
+                },
+            ),
+            // High level api: boolean ciphertexts
+            TestcaseDefinition::new(TestMetadata::HlBoolCiphertext(HL_BOOL1_TEST), |unit| {
+                let hl_client_key = Self::hl_client_key(unit, &HL_BOOL1_TEST.key_filename);
+                versioned(
+                    &FheBool::encrypt(HL_BOOL1_TEST.clear_value, &hl_client_key),
+                    "FheBool::encrypt",
+                )
+            }),
+            TestcaseDefinition::new(TestMetadata::HlBoolCiphertext(HL_BOOL2_TEST), |unit| {
+                let hl_client_key = Self::hl_client_key(unit, &HL_BOOL2_TEST.key_filename);
+                versioned(
+                    &FheBool::encrypt(HL_BOOL2_TEST.clear_value, &hl_client_key),
+                    "FheBool::encrypt",
+                )
+            }),
+            TestcaseDefinition::new(
+                TestMetadata::HlBoolCiphertext(HL_COMPRESSED_BOOL_SEEDED_TEST),
+                |unit| {
+                    let hl_client_key =
+                        Self::hl_client_key(unit, &HL_COMPRESSED_BOOL_SEEDED_TEST.key_filename);
+                    versioned(
+                        &CompressedFheBool::encrypt(
+                            HL_COMPRESSED_BOOL_SEEDED_TEST.clear_value,
+                            &hl_client_key,
+                        ),
+                        "CompressedFheBool::encrypt",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(
+                TestMetadata::HlBoolCiphertext(HL_COMPRESSED_BOOL_MODSWITCHED_TEST),
+                |unit| {
+                    let hl_client_key = Self::hl_client_key(
+                        unit,
+                        &HL_COMPRESSED_BOOL_MODSWITCHED_TEST.key_filename,
+                    );
+                    // Despite its name, the frozen data of this testcase were built with the
+                    // seeded method like the previous one, as recorded in its construction
+                    versioned(
+                        &CompressedFheBool::encrypt(
+                            HL_COMPRESSED_BOOL_MODSWITCHED_TEST.clear_value,
+                            &hl_client_key,
+                        ),
+                        "CompressedFheBool::encrypt",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(
+                TestMetadata::HlBoolCiphertext(HL_COMPACT_BOOL_TEST),
+                |unit| {
+                    let compact_pub_key = Self::hl_compact_public_key(unit);
+                    versioned(
+                        &CompactFheBool::encrypt(
+                            HL_COMPACT_BOOL_TEST.clear_value,
+                            &compact_pub_key,
+                        ),
+                        "CompactFheBool::encrypt",
+                    )
+                },
+            ),
+            TestcaseDefinition::new(
+                TestMetadata::HlBoolCiphertextList(HL_BOOL_LIST_TEST),
+                |unit| {
+                    let compact_pub_key = Self::hl_compact_public_key(unit);
+                    versioned(
+                        &CompactFheBoolList::encrypt(
+                            &HL_BOOL_LIST_TEST.clear_values,
+                            &compact_pub_key,
+                        ),
+                        "CompactFheBoolList::encrypt",
+                    )
+                },
+            ),
+        ]
+    }
+}
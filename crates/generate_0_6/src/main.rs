@@ -0,0 +1,8 @@
+mod data_0_6;
+
+use data_0_6::V0_6;
+use tfhe_backward_compat_data::generate::generate_version;
+
+fn main() {
+    generate_version::<V0_6>();
+}
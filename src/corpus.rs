@@ -0,0 +1,308 @@
+//! The sources from which the testcases and their data can be loaded: the data directory, or the
+//! data embedded in the crate with the `embedded` feature.
+
+use std::{
+    borrow::Cow,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(feature = "embedded")]
+use crate::load::load_tests_metadata_from_str;
+use crate::{
+    dir_for_version,
+    load::{check_schema_snapshot_from_str, load_tests_metadata, DataFormat, TestFailure},
+    locate_data_dir,
+    schema::schema_path,
+    TestType, Testcase, METADATA_FILES,
+};
+
+/// A set of testcases with their data, identified by their version and module instead of a path.
+///
+/// This trait is object safe, so that the corpus can be chosen at runtime (eg: a
+/// `Box<dyn Corpus>` that is embedded on wasm32 and read from the data directory elsewhere). The
+/// loaders, that are generic over the loaded type, are provided by [`CorpusLoader`].
+pub trait Corpus: Sync {
+    /// Loads the testcases of `module` that are available in this corpus
+    fn load_tests_metadata(&self, module: &str) -> Result<Vec<Testcase>, String>;
+
+    /// Checks if the data of `testcase` are available in `format`
+    fn contains(&self, testcase: &Testcase, format: DataFormat) -> bool;
+
+    /// Reads a data file of `module` generated with tfhe-rs `version`, `filename` being given
+    /// without extension
+    fn read_data(
+        &self,
+        version: &str,
+        module: &str,
+        filename: &str,
+        format: DataFormat,
+    ) -> Result<Cow<'_, [u8]>, String>;
+
+    /// Reads the schema snapshot of a test file, if there is one
+    fn read_schema_snapshot(
+        &self,
+        version: &str,
+        module: &str,
+        filename: &str,
+    ) -> Option<Cow<'_, str>>;
+
+    /// See [`DataFormat::check_version_tags`]
+    fn check_version_tags(&self, format: DataFormat, testcase: &Testcase) -> Result<(), String> {
+        let data = self.read_data(
+            &testcase.tfhe_version_min,
+            &testcase.tfhe_module,
+            &testcase.metadata.test_filename(),
+            format,
+        )?;
+        format.check_version_tags_from_bytes(&data, testcase)
+    }
+}
+
+/// The loaders of [`DataFormat`], with the data read from a [`Corpus`]. This trait is implemented
+/// for all the corpora, including `dyn Corpus`.
+pub trait CorpusLoader: Corpus {
+    /// Loads the file that should be tested, for a testcase of tfhe-rs `version`
+    fn load_versioned_test<Data: DeserializeOwned, T: TestType>(
+        &self,
+        format: DataFormat,
+        version: &str,
+        test: &T,
+    ) -> Result<Data, TestFailure> {
+        let data = self
+            .read_data(version, &test.module(), &test.test_filename(), format)
+            .map_err(|e| test.failure(e, format))?;
+        format.load_versioned_test_from_bytes(&data, test)
+    }
+
+    /// Loads auxiliary data that might be needed for a test (eg: a key to test a ciphertext).
+    /// `filename` is given without extension, as it is stored in the metadata.
+    fn load_versioned_auxiliary<Data: DeserializeOwned>(
+        &self,
+        format: DataFormat,
+        version: &str,
+        module: &str,
+        filename: &str,
+    ) -> Result<Data, String> {
+        let data = self
+            .read_data(version, module, filename, format)
+            .map_err(|e| format!("Failed to load auxiliary file: {}", e))?;
+        format.load_versioned_auxiliary_from_bytes(&data)
+    }
+
+    /// See [`check_schema_snapshot`](crate::load::check_schema_snapshot)
+    fn check_schema_snapshot<Data: Serialize, T: TestType>(
+        &self,
+        version: &str,
+        test: &T,
+        versioned: &Data,
+    ) -> Result<(), String> {
+        let snapshot = self.read_schema_snapshot(version, &test.module(), &test.test_filename());
+        check_schema_snapshot_from_str(snapshot.as_deref(), versioned)
+    }
+}
+
+impl<C: Corpus + ?Sized> CorpusLoader for C {}
+
+/// The testcases stored in a data directory, as written by the generator
+pub struct DataDirCorpus {
+    data_dir: PathBuf,
+}
+
+impl DataDirCorpus {
+    pub fn new<P: AsRef<Path>>(data_dir: P) -> Self {
+        Self {
+            data_dir: data_dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Uses the data directory found by [`locate_data_dir`]
+    pub fn locate() -> Result<Self, String> {
+        locate_data_dir().map(Self::new)
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    fn module_dir(&self, version: &str, module: &str) -> PathBuf {
+        dir_for_version(&self.data_dir, version).join(module)
+    }
+}
+
+impl Corpus for DataDirCorpus {
+    fn load_tests_metadata(&self, module: &str) -> Result<Vec<Testcase>, String> {
+        let (_, filename) = METADATA_FILES
+            .iter()
+            .find(|(name, _)| *name == module)
+            .ok_or_else(|| format!("Unknown module {}", module))?;
+
+        load_tests_metadata(self.data_dir.join(filename))
+    }
+
+    fn contains(&self, testcase: &Testcase, format: DataFormat) -> bool {
+        format
+            .path_in(
+                self.module_dir(&testcase.tfhe_version_min, &testcase.tfhe_module),
+                &testcase.metadata.test_filename(),
+            )
+            .is_file()
+    }
+
+    fn read_data(
+        &self,
+        version: &str,
+        module: &str,
+        filename: &str,
+        format: DataFormat,
+    ) -> Result<Cow<'_, [u8]>, String> {
+        let path = format.path_in(self.module_dir(version, module), filename);
+        fs::read(&path)
+            .map(Cow::Owned)
+            .map_err(|e| format!("Failed to read file {}: {}", path.display(), e))
+    }
+
+    fn read_schema_snapshot(
+        &self,
+        version: &str,
+        module: &str,
+        filename: &str,
+    ) -> Option<Cow<'_, str>> {
+        fs::read_to_string(schema_path(self.module_dir(version, module), filename))
+            .ok()
+            .map(Cow::Owned)
+    }
+}
+
+pub use crate::filter::{DEFAULT_EMBED_FILTER, EMBED_FILTER_ENV_VAR};
+
+#[cfg(feature = "embedded")]
+mod embedded {
+    // Generated by the build script
+    include!(concat!(env!("OUT_DIR"), "/embedded_corpus.rs"));
+}
+
+/// The testcases embedded in the crate at build time, selected by [`EMBED_FILTER_ENV_VAR`]. This
+/// corpus does not need the data directory, so it can be used in sandboxes or on wasm32.
+#[cfg(feature = "embedded")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EmbeddedCorpus;
+
+#[cfg(feature = "embedded")]
+impl EmbeddedCorpus {
+    /// The filter used to select the embedded data
+    pub fn filter(&self) -> &'static str {
+        embedded::FILTER
+    }
+
+    fn file(
+        &self,
+        version: &str,
+        module: &str,
+        filename: &str,
+        format: DataFormat,
+    ) -> Option<&'static [u8]> {
+        embedded::FILES
+            .iter()
+            .find(|(file_version, file_module, file_name, extension, _)| {
+                *file_version == version
+                    && *file_module == module
+                    && *file_name == filename
+                    && *extension == format.extension()
+            })
+            .map(|(_, _, _, _, data)| *data)
+    }
+}
+
+#[cfg(feature = "embedded")]
+impl Corpus for EmbeddedCorpus {
+    /// Only the testcases for which some data are embedded are returned
+    fn load_tests_metadata(&self, module: &str) -> Result<Vec<Testcase>, String> {
+        let (_, serialized) = embedded::METADATA
+            .iter()
+            .find(|(name, _)| *name == module)
+            .ok_or_else(|| format!("Unknown module {}", module))?;
+
+        let mut testcases = load_tests_metadata_from_str(serialized)?;
+        testcases.retain(|testcase| {
+            DataFormat::ALL
+                .iter()
+                .any(|format| self.contains(testcase, *format))
+        });
+        Ok(testcases)
+    }
+
+    fn contains(&self, testcase: &Testcase, format: DataFormat) -> bool {
+        self.file(
+            &testcase.tfhe_version_min,
+            &testcase.tfhe_module,
+            &testcase.metadata.test_filename(),
+            format,
+        )
+        .is_some()
+    }
+
+    fn read_data(
+        &self,
+        version: &str,
+        module: &str,
+        filename: &str,
+        format: DataFormat,
+    ) -> Result<Cow<'_, [u8]>, String> {
+        self.file(version, module, filename, format)
+            .map(Cow::Borrowed)
+            .ok_or_else(|| {
+                format!(
+                    "{}/{}/{}.{} is not embedded, it was excluded by the filter `{}` of {}",
+                    version,
+                    module,
+                    filename,
+                    format.extension(),
+                    self.filter(),
+                    EMBED_FILTER_ENV_VAR
+                )
+            })
+    }
+
+    fn read_schema_snapshot(
+        &self,
+        version: &str,
+        module: &str,
+        filename: &str,
+    ) -> Option<Cow<'_, str>> {
+        embedded::SCHEMAS
+            .iter()
+            .find(|(file_version, file_module, file_name, _)| {
+                *file_version == version && *file_module == module && *file_name == filename
+            })
+            .map(|(_, _, _, snapshot)| Cow::Borrowed(*snapshot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::temp_dir;
+
+    #[test]
+    fn loaders_are_available_on_dyn_corpus() {
+        let data_dir = temp_dir("corpus_dyn");
+        let module_dir = dir_for_version(&data_dir, "0.6").join("shortint");
+        fs::create_dir_all(&module_dir).unwrap();
+        let mut serialized = Vec::new();
+        ciborium::ser::into_writer(&42u64, &mut serialized).unwrap();
+        fs::write(module_dir.join("key.cbor"), serialized).unwrap();
+
+        let corpus: Box<dyn Corpus> = Box::new(DataDirCorpus::new(&data_dir));
+        let loaded =
+            corpus.load_versioned_auxiliary::<u64>(DataFormat::Cbor, "0.6", "shortint", "key");
+        let missing =
+            corpus.load_versioned_auxiliary::<u64>(DataFormat::Bincode, "0.6", "shortint", "key");
+        fs::remove_dir_all(&data_dir).unwrap();
+
+        assert_eq!(loaded, Ok(42));
+        assert!(missing.is_err());
+    }
+}
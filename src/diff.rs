@@ -0,0 +1,424 @@
+use std::{collections::BTreeMap, fmt::Display, path::Path};
+
+use crate::{
+    dir_for_version,
+    inspect::{inspect_cbor_file, InspectNode},
+    load_all_metadata, Testcase,
+};
+
+/// A difference between the structures of two serialized values. The values themselves are
+/// ignored, since most of them are random.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StructuralChange {
+    /// A field was added to a struct
+    AddedField { path: String, field: String },
+    /// A field was removed from a struct
+    RemovedField { path: String, field: String },
+    /// The fields of a struct are serialized in a different order
+    ReorderedFields {
+        path: String,
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+    /// The version tag of a versioned type changed
+    ChangedVersion {
+        path: String,
+        old: String,
+        new: String,
+    },
+    /// The length of an array changed
+    ChangedLength {
+        path: String,
+        old: usize,
+        new: usize,
+    },
+    /// The value is of a different kind (eg: an array replaced by a struct)
+    ChangedKind {
+        path: String,
+        old: &'static str,
+        new: &'static str,
+    },
+}
+
+/// How the root of the value is named in the paths of the changes
+const ROOT_PATH: &str = "<root>";
+
+impl Display for StructuralChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AddedField { path, field } => write!(f, "{}: added field `{}`", path, field),
+            Self::RemovedField { path, field } => {
+                write!(f, "{}: removed field `{}`", path, field)
+            }
+            Self::ReorderedFields { path, old, new } => write!(
+                f,
+                "{}: fields reordered from ({}) to ({})",
+                path,
+                old.join(", "),
+                new.join(", ")
+            ),
+            Self::ChangedVersion { path, old, new } => {
+                write!(f, "{}: version changed from {} to {}", path, old, new)
+            }
+            Self::ChangedLength { path, old, new } => {
+                write!(f, "{}: array length changed from {} to {}", path, old, new)
+            }
+            Self::ChangedKind { path, old, new } => {
+                write!(f, "{}: changed from {} to {}", path, old, new)
+            }
+        }
+    }
+}
+
+fn kind_name(node: &InspectNode) -> &'static str {
+    match node {
+        InspectNode::Versioned { .. } => "versioned value",
+        InspectNode::Map(_) => "struct",
+        InspectNode::Seq { .. } => "array",
+        InspectNode::Bytes { .. } => "bytes",
+        InspectNode::Integer(_) => "integer",
+        InspectNode::Float(_) => "float",
+        InspectNode::Bool(_) => "bool",
+        InspectNode::Text(_) => "text",
+        InspectNode::Null => "null",
+    }
+}
+
+fn diff_at(path: &str, old: &InspectNode, new: &InspectNode, changes: &mut Vec<StructuralChange>) {
+    match (old, new) {
+        (
+            InspectNode::Versioned {
+                tag: old_tag,
+                value: old_value,
+            },
+            InspectNode::Versioned {
+                tag: new_tag,
+                value: new_value,
+            },
+        ) => {
+            if old_tag != new_tag {
+                changes.push(StructuralChange::ChangedVersion {
+                    path: path.to_string(),
+                    old: old_tag.clone(),
+                    new: new_tag.clone(),
+                });
+            }
+            diff_at(path, old_value, new_value, changes);
+        }
+        (InspectNode::Map(old_fields), InspectNode::Map(new_fields)) => {
+            let old_names: Vec<String> = old_fields.iter().map(|(name, _)| name.clone()).collect();
+            let new_names: Vec<String> = new_fields.iter().map(|(name, _)| name.clone()).collect();
+
+            for name in old_names.iter().filter(|name| !new_names.contains(name)) {
+                changes.push(StructuralChange::RemovedField {
+                    path: path.to_string(),
+                    field: name.clone(),
+                });
+            }
+            for name in new_names.iter().filter(|name| !old_names.contains(name)) {
+                changes.push(StructuralChange::AddedField {
+                    path: path.to_string(),
+                    field: name.clone(),
+                });
+            }
+
+            // The order of the fields matters for bincode
+            let old_common: Vec<String> = old_names
+                .iter()
+                .filter(|name| new_names.contains(name))
+                .cloned()
+                .collect();
+            let new_common: Vec<String> = new_names
+                .iter()
+                .filter(|name| old_names.contains(name))
+                .cloned()
+                .collect();
+            if old_common != new_common {
+                changes.push(StructuralChange::ReorderedFields {
+                    path: path.to_string(),
+                    old: old_common,
+                    new: new_common,
+                });
+            }
+
+            for (name, old_value) in old_fields {
+                if let Some((_, new_value)) = new_fields.iter().find(|(field, _)| field == name) {
+                    let field_path = if path == ROOT_PATH {
+                        name.clone()
+                    } else {
+                        format!("{}.{}", path, name)
+                    };
+                    diff_at(&field_path, old_value, new_value, changes);
+                }
+            }
+        }
+        (
+            InspectNode::Seq {
+                len: old_len,
+                items: old_items,
+            },
+            InspectNode::Seq {
+                len: new_len,
+                items: new_items,
+            },
+        ) => {
+            if old_len != new_len {
+                changes.push(StructuralChange::ChangedLength {
+                    path: path.to_string(),
+                    old: *old_len,
+                    new: *new_len,
+                });
+            }
+
+            // The items of an array have the same type, so only the first ones are compared
+            if let (Some(old_item), Some(new_item)) = (old_items.first(), new_items.first()) {
+                diff_at(&format!("{}[]", path), old_item, new_item, changes);
+            }
+        }
+        (InspectNode::Bytes { len: old_len }, InspectNode::Bytes { len: new_len }) => {
+            if old_len != new_len {
+                changes.push(StructuralChange::ChangedLength {
+                    path: path.to_string(),
+                    old: *old_len,
+                    new: *new_len,
+                });
+            }
+        }
+        (old, new) => {
+            let (old_kind, new_kind) = (kind_name(old), kind_name(new));
+            if old_kind != new_kind {
+                changes.push(StructuralChange::ChangedKind {
+                    path: path.to_string(),
+                    old: old_kind,
+                    new: new_kind,
+                });
+            }
+        }
+    }
+}
+
+/// Lists the structural differences between two values: added or removed fields, changed
+/// version tags and changed array lengths. The values of the scalars are ignored.
+pub fn diff_nodes(old: &InspectNode, new: &InspectNode) -> Vec<StructuralChange> {
+    let mut changes = Vec::new();
+    diff_at(ROOT_PATH, old, new, &mut changes);
+    changes
+}
+
+/// Lists the structural differences between two cbor files
+pub fn diff_cbor_files<P1: AsRef<Path>, P2: AsRef<Path>>(
+    old: P1,
+    new: P2,
+) -> Result<Vec<StructuralChange>, String> {
+    Ok(diff_nodes(
+        &inspect_cbor_file(old)?,
+        &inspect_cbor_file(new)?,
+    ))
+}
+
+/// How a testcase differs between two versions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestcaseDiff {
+    /// The testcase only exists in the new version
+    Added,
+    /// The testcase only exists in the old version
+    Removed,
+    /// The structure of the data changed
+    Changed(Vec<StructuralChange>),
+}
+
+/// Testcases are matched between versions by their module, type and filename
+fn testcase_key(testcase: &Testcase) -> String {
+    format!(
+        "{}::{} ({})",
+        testcase.tfhe_module,
+        testcase.metadata,
+        testcase.metadata.test_filename()
+    )
+}
+
+/// Compares the cbor data of the testcases of two versions stored in `data_dir`. The testcases
+/// are matched by module, type and filename, and only the ones that differ are returned.
+pub fn diff_versions<P: AsRef<Path>>(
+    data_dir: P,
+    old_version: &str,
+    new_version: &str,
+) -> Result<Vec<(String, TestcaseDiff)>, String> {
+    let data_dir = data_dir.as_ref();
+    let testcases = load_all_metadata(data_dir)?;
+
+    let version_testcases = |version: &str| -> BTreeMap<String, &Testcase> {
+        testcases
+            .iter()
+            .filter(|testcase| testcase.tfhe_version_min == version)
+            .map(|testcase| (testcase_key(testcase), testcase))
+            .collect()
+    };
+    let old_testcases = version_testcases(old_version);
+    let new_testcases = version_testcases(new_version);
+
+    let cbor_path = |testcase: &Testcase| {
+        dir_for_version(data_dir, &testcase.tfhe_version_min)
+            .join(&testcase.tfhe_module)
+            .join(format!("{}.cbor", testcase.metadata.test_filename()))
+    };
+
+    let mut diffs = Vec::new();
+    for (key, old_testcase) in old_testcases.iter() {
+        match new_testcases.get(key) {
+            Some(new_testcase) => {
+                let changes = diff_cbor_files(cbor_path(old_testcase), cbor_path(new_testcase))?;
+                if !changes.is_empty() {
+                    diffs.push((key.clone(), TestcaseDiff::Changed(changes)));
+                }
+            }
+            None => diffs.push((key.clone(), TestcaseDiff::Removed)),
+        }
+    }
+
+    for key in new_testcases.keys() {
+        if !old_testcases.contains_key(key) {
+            diffs.push((key.clone(), TestcaseDiff::Added));
+        }
+    }
+
+    Ok(diffs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(fields: &[(&str, InspectNode)]) -> InspectNode {
+        InspectNode::Map(
+            fields
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect(),
+        )
+    }
+
+    fn versioned(tag: &str, value: InspectNode) -> InspectNode {
+        InspectNode::Versioned {
+            tag: tag.to_string(),
+            value: Box::new(value),
+        }
+    }
+
+    fn seq(len: usize, items: &[InspectNode]) -> InspectNode {
+        InspectNode::Seq {
+            len,
+            items: items.to_vec(),
+        }
+    }
+
+    #[test]
+    fn values_are_ignored() {
+        let old = versioned(
+            "V0",
+            map(&[
+                ("a", InspectNode::Integer(1)),
+                ("b", seq(4, &[InspectNode::Text("x".to_string())])),
+            ]),
+        );
+        let new = versioned(
+            "V0",
+            map(&[
+                ("a", InspectNode::Integer(2)),
+                ("b", seq(4, &[InspectNode::Text("y".to_string())])),
+            ]),
+        );
+
+        assert_eq!(diff_nodes(&old, &new), Vec::new());
+    }
+
+    #[test]
+    fn field_changes_are_reported() {
+        let old = map(&[
+            ("a", InspectNode::Null),
+            ("b", InspectNode::Null),
+            ("c", InspectNode::Null),
+            ("nested", map(&[("removed", InspectNode::Null)])),
+        ]);
+        let new = map(&[
+            ("c", InspectNode::Null),
+            ("b", InspectNode::Null),
+            ("nested", map(&[("added", InspectNode::Null)])),
+        ]);
+
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        assert_eq!(
+            diff_nodes(&old, &new),
+            vec![
+                StructuralChange::RemovedField {
+                    path: ROOT_PATH.to_string(),
+                    field: "a".to_string(),
+                },
+                StructuralChange::ReorderedFields {
+                    path: ROOT_PATH.to_string(),
+                    old: names(&["b", "c", "nested"]),
+                    new: names(&["c", "b", "nested"]),
+                },
+                StructuralChange::RemovedField {
+                    path: "nested".to_string(),
+                    field: "removed".to_string(),
+                },
+                StructuralChange::AddedField {
+                    path: "nested".to_string(),
+                    field: "added".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn version_length_and_kind_changes_are_reported() {
+        let old = versioned(
+            "V0",
+            map(&[
+                ("keys", seq(3, &[versioned("V0", InspectNode::Null)])),
+                ("data", InspectNode::Bytes { len: 8 }),
+                ("value", InspectNode::Integer(1)),
+            ]),
+        );
+        let new = versioned(
+            "V1",
+            map(&[
+                ("keys", seq(2, &[versioned("V1", InspectNode::Null)])),
+                ("data", InspectNode::Bytes { len: 16 }),
+                ("value", seq(0, &[])),
+            ]),
+        );
+
+        assert_eq!(
+            diff_nodes(&old, &new),
+            vec![
+                StructuralChange::ChangedVersion {
+                    path: ROOT_PATH.to_string(),
+                    old: "V0".to_string(),
+                    new: "V1".to_string(),
+                },
+                StructuralChange::ChangedLength {
+                    path: "keys".to_string(),
+                    old: 3,
+                    new: 2,
+                },
+                StructuralChange::ChangedVersion {
+                    path: "keys[]".to_string(),
+                    old: "V0".to_string(),
+                    new: "V1".to_string(),
+                },
+                StructuralChange::ChangedLength {
+                    path: "data".to_string(),
+                    old: 8,
+                    new: 16,
+                },
+                StructuralChange::ChangedKind {
+                    path: "value".to_string(),
+                    old: "integer",
+                    new: "array",
+                },
+            ]
+        );
+    }
+}
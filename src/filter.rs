@@ -0,0 +1,264 @@
+//! The selection of the tests by tag, module, type, version and format.
+//!
+//! The build script includes this file to select the embedded data with the same filters as the
+//! tests, so it should only use `std` and [`layout`](crate::layout).
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+};
+
+use crate::layout::DATA_EXTENSIONS;
+
+/// Environment variable holding the filter that selects the data embedded by the `embedded`
+/// feature, in the syntax of [`TestFilter::parse`]. The auxiliaries of the selected testcases are
+/// always embedded.
+pub const EMBED_FILTER_ENV_VAR: &str = "TFHE_BACKWARD_COMPAT_EMBED_FILTER";
+
+/// The filter used to select the embedded data when [`EMBED_FILTER_ENV_VAR`] is not set. Only the
+/// fast testcases are embedded by default, to keep the crate small. Set the variable to an empty
+/// string to embed everything.
+pub const DEFAULT_EMBED_FILTER: &str = "tag:fast";
+
+/// What is known of a test when it is selected: a testcase in one of the formats
+pub struct FilteredTest<'a> {
+    /// The version of tfhe-rs that generated the data
+    pub version: &'a str,
+    pub module: &'a str,
+    /// The name of the kind of test, as written in the metadata (eg: `HlCiphertext`)
+    pub kind: &'a str,
+    pub tags: &'a BTreeSet<String>,
+    /// The extension of the files of the format (eg: `bcode`)
+    pub extension: &'a str,
+}
+
+/// A criterion on which the tests can be selected
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum FilterCriterion {
+    Tag(String),
+    Module(String),
+    /// The type of test, the name of the variant of `TestMetadata` (eg: `HlCiphertext`)
+    Type(String),
+    /// The version of tfhe-rs that generated the data
+    Version(String),
+    /// The extension of the files of the format
+    Format(&'static str),
+}
+
+impl FilterCriterion {
+    fn parse(term: &str) -> Result<Self, String> {
+        let (key, value) = term
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid filter `{}`, expected `key:value`", term))?;
+
+        if value.is_empty() {
+            return Err(format!("Invalid filter `{}`, the value is empty", term));
+        }
+
+        match key {
+            "tag" => Ok(Self::Tag(value.to_string())),
+            "module" => Ok(Self::Module(value.to_string())),
+            "type" => Ok(Self::Type(value.to_string())),
+            "version" => Ok(Self::Version(value.to_string())),
+            "format" => {
+                let extension = if value == "bincode" { "bcode" } else { value };
+                DATA_EXTENSIONS
+                    .into_iter()
+                    .find(|known| *known == extension)
+                    .map(Self::Format)
+                    .ok_or_else(|| format!("Invalid filter `{}`, unknown format", term))
+            }
+            _ => Err(format!(
+                "Invalid filter `{}`, expected one of tag, module, type, version or format",
+                term
+            )),
+        }
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Tag(_) => "tag",
+            Self::Module(_) => "module",
+            Self::Type(_) => "type",
+            Self::Version(_) => "version",
+            Self::Format(_) => "format",
+        }
+    }
+
+    fn matches(&self, test: &FilteredTest) -> bool {
+        match self {
+            Self::Tag(tag) => test.tags.contains(tag),
+            Self::Module(module) => test.module == module,
+            Self::Type(test_type) => test.kind == test_type,
+            Self::Version(version) => test.version == version,
+            Self::Format(extension) => test.extension == *extension,
+        }
+    }
+}
+
+impl Display for FilterCriterion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tag(value) | Self::Module(value) | Self::Type(value) | Self::Version(value) => {
+                write!(f, "{}:{}", self.key(), value)
+            }
+            Self::Format(extension) => write!(f, "{}:{}", self.key(), extension),
+        }
+    }
+}
+
+/// Selection of the tests to run, by tag, module, type, version and format
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TestFilter {
+    included: Vec<FilterCriterion>,
+    excluded: Vec<FilterCriterion>,
+}
+
+impl TestFilter {
+    /// Parses a list of comma separated terms of the form `key:value`, where `key` is one of
+    /// `tag`, `module`, `type`, `version` or `format` (eg: `tag:fast,format:cbor`). A term
+    /// prefixed by `!` excludes the tests it matches (eg: `!tag:slow`).
+    ///
+    /// The values of `type` are the names of the variants of `TestMetadata`, such as
+    /// `ShortintCiphertext`, `HlCiphertext` or `HlClientKey`. The values of `format` are `cbor`
+    /// and `bcode` (or `bincode`).
+    ///
+    /// A test is selected if it matches at least one of the terms of each key, and none of the
+    /// excluded terms. An empty filter selects all the tests.
+    pub fn parse(filter: &str) -> Result<Self, String> {
+        let mut result = Self::default();
+
+        for term in filter
+            .split(',')
+            .map(str::trim)
+            .filter(|term| !term.is_empty())
+        {
+            match term.strip_prefix('!') {
+                Some(excluded) => result.excluded.push(FilterCriterion::parse(excluded)?),
+                None => result.included.push(FilterCriterion::parse(term)?),
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.included.is_empty() && self.excluded.is_empty()
+    }
+
+    /// Returns the reason why `test` is not selected, or `None` if it is selected
+    pub fn rejection_reason_of(&self, test: &FilteredTest) -> Option<String> {
+        if let Some(criterion) = self
+            .excluded
+            .iter()
+            .find(|criterion| criterion.matches(test))
+        {
+            return Some(format!(
+                "{} file excluded by the filter `!{}`",
+                test.extension, criterion
+            ));
+        }
+
+        let keys: BTreeSet<&str> = self.included.iter().map(FilterCriterion::key).collect();
+        keys.into_iter().find_map(|key| {
+            let criteria: Vec<&FilterCriterion> = self
+                .included
+                .iter()
+                .filter(|criterion| criterion.key() == key)
+                .collect();
+
+            if criteria.iter().any(|criterion| criterion.matches(test)) {
+                None
+            } else {
+                let criteria: Vec<String> = criteria
+                    .iter()
+                    .map(|criterion| criterion.to_string())
+                    .collect();
+                Some(format!(
+                    "{} file not selected by the filter `{}`",
+                    test.extension,
+                    criteria.join(",")
+                ))
+            }
+        })
+    }
+
+    pub fn selects_test(&self, test: &FilteredTest) -> bool {
+        self.rejection_reason_of(test).is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test<'a>(tags: &'a BTreeSet<String>, kind: &'a str, extension: &'a str) -> FilteredTest<'a> {
+        FilteredTest {
+            version: "0.6",
+            module: "high_level_api",
+            kind,
+            tags,
+            extension,
+        }
+    }
+
+    #[test]
+    fn invalid_filters_are_rejected() {
+        for filter in [
+            "fast",
+            "tag:",
+            "speed:fast",
+            "format:json",
+            "!module",
+            "tag:fast,version",
+        ] {
+            assert!(
+                TestFilter::parse(filter).is_err(),
+                "{} should be invalid",
+                filter
+            );
+        }
+    }
+
+    #[test]
+    fn empty_filters_select_everything() {
+        for filter in ["", " , ,"] {
+            let parsed = TestFilter::parse(filter).unwrap();
+            assert!(parsed.is_empty());
+
+            let tags = BTreeSet::new();
+            assert!(parsed.selects_test(&test(&tags, "HlCiphertext", "cbor")));
+        }
+    }
+
+    #[test]
+    fn terms_of_a_key_are_alternatives_and_keys_are_combined() {
+        let filter = TestFilter::parse("tag:fast, tag:key ,format:bincode").unwrap();
+        let fast = BTreeSet::from(["fast".to_string()]);
+        let slow = BTreeSet::from(["slow".to_string()]);
+        let key = BTreeSet::from(["key".to_string(), "slow".to_string()]);
+
+        assert!(filter.selects_test(&test(&fast, "HlCiphertext", "bcode")));
+        assert!(filter.selects_test(&test(&key, "HlClientKey", "bcode")));
+        assert_eq!(
+            filter.rejection_reason_of(&test(&fast, "HlCiphertext", "cbor")),
+            Some("cbor file not selected by the filter `format:bcode`".to_string())
+        );
+        assert_eq!(
+            filter.rejection_reason_of(&test(&slow, "HlCiphertext", "bcode")),
+            Some("bcode file not selected by the filter `tag:fast,tag:key`".to_string())
+        );
+    }
+
+    #[test]
+    fn excluded_terms_take_precedence() {
+        let filter = TestFilter::parse("module:high_level_api,!type:HlClientKey").unwrap();
+        let tags = BTreeSet::new();
+
+        assert!(filter.selects_test(&test(&tags, "HlCiphertext", "cbor")));
+        assert_eq!(
+            filter.rejection_reason_of(&test(&tags, "HlClientKey", "cbor")),
+            Some("cbor file excluded by the filter `!type:HlClientKey`".to_string())
+        );
+    }
+}
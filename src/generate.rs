@@ -1,14 +1,27 @@
 use std::{
     borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    env,
     fs::{self, File},
     path::{Path, PathBuf},
+    process::exit,
 };
 
 use bincode::Options;
-use serde::Serialize;
-use tfhe_versionable::Versionize;
+use serde::{Deserialize, Serialize};
 
-use crate::{data_dir, dir_for_version, TestMetadata, TestParameterSet};
+use crate::{
+    check_references, check_unique_testcases, data_dir, dir_for_version,
+    inspect::inspect_cbor_file,
+    metadata::MetadataFile,
+    parallel_map,
+    schema::{schema_path, Schema},
+    StoredType, TestMetadata, TestParameterSet, Testcase, VersionTags, DATA_EXTENSIONS, FAST_TAG,
+    MANIFEST_FILENAME, SLOW_TAG,
+};
+
+/// Global seed from which the seed of each testcase is derived
+pub const PRNG_SEED: u128 = 0xdeadbeef;
 
 /// Valid parameter set that can be used in tfhe operations
 pub const VALID_TEST_PARAMS: TestParameterSet = TestParameterSet {
@@ -59,21 +72,71 @@ pub fn save_bcode<Data: Serialize, P: AsRef<Path>>(msg: &Data, path: P) {
     options.serialize_into(&mut file, msg).unwrap();
 }
 
-/// Stores the test data in `dir`, encoded in both cbor and bincode
-pub fn store_versioned_test<Data: Versionize, P: AsRef<Path>>(
-    msg: &Data,
+/// A versioned object, encoded in all the formats used to store the data, with the snapshot of
+/// its schema
+pub struct VersionedData {
+    cbor: Vec<u8>,
+    bincode: Vec<u8>,
+    schema: Schema,
+    stored_type: Option<StoredType>,
+}
+
+impl VersionedData {
+    /// Encodes the versioned data in cbor and bincode.
+    /// Generator crates should versionize their tfhe-rs objects with their own version of
+    /// `tfhe-versionable` before calling this.
+    pub fn new<Data: Serialize>(versioned: &Data) -> Self {
+        let mut cbor = Vec::new();
+        ciborium::ser::into_writer(versioned, &mut cbor).unwrap();
+
+        let options = bincode::DefaultOptions::new().with_fixint_encoding();
+        let bincode = options.serialize(versioned).unwrap();
+
+        let schema = Schema::of_value(versioned).unwrap();
+
+        Self {
+            cbor,
+            bincode,
+            schema,
+            stored_type: None,
+        }
+    }
+
+    /// Records the type of the tfhe-rs object that was versionized, and how it was built
+    pub fn with_stored_type(self, stored_type: StoredType) -> Self {
+        Self {
+            stored_type: Some(stored_type),
+            ..self
+        }
+    }
+
+    pub fn stored_type(&self) -> Option<&StoredType> {
+        self.stored_type.as_ref()
+    }
+
+    /// Stores the data in `dir`, in one file per format, and the schema next to them
+    pub fn store<P: AsRef<Path>>(&self, dir: P, test_filename: &str) {
+        // Store in cbor
+        let filename_cbor = format!("{}.cbor", test_filename);
+        fs::write(dir.as_ref().join(filename_cbor), &self.cbor).unwrap();
+
+        // Store in bincode
+        let filename_bincode = format!("{}.bcode", test_filename);
+        fs::write(dir.as_ref().join(filename_bincode), &self.bincode).unwrap();
+
+        self.schema.store(schema_path(dir, test_filename));
+    }
+}
+
+/// Stores the versioned test data in `dir`, encoded in both cbor and bincode.
+/// Generator crates should versionize their tfhe-rs objects with their own version of
+/// `tfhe-versionable` before calling this.
+pub fn store_versioned_test<Data: Serialize, P: AsRef<Path>>(
+    versioned: &Data,
     dir: P,
     test_filename: &str,
 ) {
-    let versioned = msg.versionize();
-
-    // Store in cbor
-    let filename_cbor = format!("{}.cbor", test_filename);
-    save_cbor(&versioned, dir.as_ref().join(filename_cbor));
-
-    // Store in bincode
-    let filename_bincode = format!("{}.bcode", test_filename);
-    save_bcode(&versioned, dir.as_ref().join(filename_bincode));
+    VersionedData::new(versioned).store(dir, test_filename);
 }
 
 pub fn store_metadata<Meta: Serialize, P: AsRef<Path>>(value: &Meta, path: P) {
@@ -81,24 +144,856 @@ pub fn store_metadata<Meta: Serialize, P: AsRef<Path>>(value: &Meta, path: P) {
     fs::write(path, serialized).unwrap();
 }
 
+/// Finds the Cargo.lock used to build the running generator. Each generator crate is its own
+/// workspace, and `cargo run` gives the directory of the crate in `CARGO_MANIFEST_DIR`.
+fn generator_lockfile() -> Result<PathBuf, String> {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").map_err(|_| {
+        "CARGO_MANIFEST_DIR is not set, the generator should be run with cargo".to_string()
+    })?;
+
+    let lockfile_path = Path::new(&manifest_dir).join("Cargo.lock");
+    if lockfile_path.is_file() {
+        Ok(lockfile_path)
+    } else {
+        Err(format!(
+            "No Cargo.lock found for the generator in {}",
+            manifest_dir
+        ))
+    }
+}
+
+/// Reads the version tags of the versioned object stored in a cbor file
+fn stored_version_tags<P: AsRef<Path>>(cbor_path: P) -> Result<Option<VersionTags>, String> {
+    inspect_cbor_file(cbor_path).map(|node| Schema::from_node(&node).version_tags())
+}
+
+/// Records the version tags of the `testcases` that have none, reading them from the cbor data
+/// stored in `data_dir`. This is used for the data generated before the tags were recorded.
+/// Returns the number of testcases that were updated.
+pub fn backfill_version_tags<P: AsRef<Path>>(
+    data_dir: P,
+    testcases: &mut [Testcase],
+) -> Result<usize, String> {
+    let mut updated = 0;
+
+    for testcase in testcases
+        .iter_mut()
+        .filter(|testcase| testcase.version_tags.is_none())
+    {
+        let cbor_path = dir_for_version(data_dir.as_ref(), &testcase.tfhe_version_min)
+            .join(&testcase.tfhe_module)
+            .join(format!("{}.cbor", testcase.metadata.test_filename()));
+
+        testcase.version_tags =
+            Some(stored_version_tags(&cbor_path)?.ok_or_else(|| {
+                format!("{} does not hold a versioned object", cbor_path.display())
+            })?);
+        updated += 1;
+    }
+
+    Ok(updated)
+}
+
+/// Writes the schema snapshots that are missing for `testcases`, extracting them from the cbor
+/// data stored in `data_dir`. This is used for the data generated before the snapshots were
+/// introduced, that cannot be generated again. Returns the paths of the written snapshots.
+pub fn backfill_schema_snapshots<P: AsRef<Path>>(
+    data_dir: P,
+    testcases: &[Testcase],
+) -> Result<Vec<PathBuf>, String> {
+    let mut written = Vec::new();
+
+    for testcase in testcases {
+        let module_dir = dir_for_version(data_dir.as_ref(), &testcase.tfhe_version_min)
+            .join(&testcase.tfhe_module);
+        let test_filename = testcase.metadata.test_filename();
+
+        let path = schema_path(&module_dir, &test_filename);
+        if path.exists() {
+            continue;
+        }
+
+        let node = inspect_cbor_file(module_dir.join(format!("{}.cbor", test_filename)))?;
+        Schema::from_node(&node).store(&path);
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// Returns the version and source (eg: the git revision) of the package `name` with the given
+/// `version` in the Cargo.lock of the running generator
+pub fn locked_dependency_revision(name: &str, version: &str) -> Result<String, String> {
+    let lockfile_path = generator_lockfile()?;
+    let lockfile = fs::read_to_string(&lockfile_path)
+        .map_err(|e| format!("Failed to read {}: {}", lockfile_path.display(), e))?;
+
+    lockfile
+        .split("[[package]]")
+        .find_map(|package| {
+            let field = |key: &str| {
+                package.lines().find_map(|line| {
+                    let (line_key, value) = line.split_once('=')?;
+                    (line_key.trim() == key).then(|| value.trim().trim_matches('"').to_string())
+                })
+            };
+
+            let package_version = field("version")?;
+            let matches_version =
+                package_version == version || package_version.starts_with(&format!("{}.", version));
+
+            if field("name")? != name || !matches_version {
+                return None;
+            }
+
+            Some(match field("source") {
+                Some(source) => format!("{} ({})", package_version, source),
+                None => package_version,
+            })
+        })
+        .ok_or_else(|| {
+            format!(
+                "Package {} {} not found in {}",
+                name,
+                version,
+                lockfile_path.display()
+            )
+        })
+}
+
+/// The inputs that were used to generate a testcase. If one of them changes, the testcase should
+/// be generated again.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ManifestEntry {
+    /// The metadata of the testcase, serialized in ron
+    pub metadata: String,
+    pub seed: u128,
+    pub tfhe_revision: String,
+    /// The inputs of the auxiliaries used by the testcase (eg: the key used to encrypt a
+    /// ciphertext), indexed by their test filename. If an auxiliary changes, the data of the
+    /// testcase are not valid anymore.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub auxiliaries: BTreeMap<String, ManifestEntry>,
+    /// The type of the generated object. This is an output of the generation, so it is not
+    /// compared to decide if the testcase is up to date.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stored_type: Option<StoredType>,
+}
+
+impl ManifestEntry {
+    /// Returns true if both entries were generated with the same inputs
+    pub fn has_same_inputs(&self, other: &Self) -> bool {
+        self.metadata == other.metadata
+            && self.seed == other.seed
+            && self.tfhe_revision == other.tfhe_revision
+            && self.auxiliaries.len() == other.auxiliaries.len()
+            && self.auxiliaries.iter().all(|(filename, auxiliary)| {
+                other
+                    .auxiliaries
+                    .get(filename)
+                    .is_some_and(|other_auxiliary| auxiliary.has_same_inputs(other_auxiliary))
+            })
+    }
+}
+
+/// The list of the testcases that were generated for a version, with the inputs used to generate
+/// them
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GenerationManifest {
+    /// Entries indexed by "module/test_filename"
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub testcases: BTreeMap<String, ManifestEntry>,
+    /// The types of the objects stored for the testcases of a frozen version, indexed like
+    /// `testcases`. The inputs used to generate the data of a frozen version are not known, so
+    /// these testcases have no entry in `testcases`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub frozen_stored_types: BTreeMap<String, StoredType>,
+}
+
+impl GenerationManifest {
+    /// Loads the manifest stored at `path`, or returns an empty one if the file does not exist
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let serialized = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read manifest {}: {}", path.display(), e))?;
+        ron::from_str(&serialized)
+            .map_err(|e| format!("Invalid manifest {}: {}", path.display(), e))
+    }
+
+    pub fn store<P: AsRef<Path>>(&self, path: P) {
+        let serialized = ron::ser::to_string_pretty(self, Default::default()).unwrap();
+        fs::write(path, serialized).unwrap();
+    }
+
+    /// Returns true if `unit` has already been generated with the same inputs and its files are
+    /// still there
+    pub fn is_up_to_date(&self, unit: &GenerationUnit) -> bool {
+        self.testcases
+            .get(&unit.manifest_key())
+            .is_some_and(|entry| entry.has_same_inputs(&unit.manifest_entry()))
+            && unit.data_files().iter().all(|file| file.exists())
+    }
+
+    /// Records that `unit` has been generated, as an object of type `stored_type`
+    pub fn update(&mut self, unit: &GenerationUnit, stored_type: Option<StoredType>) {
+        self.testcases.insert(
+            unit.manifest_key(),
+            ManifestEntry {
+                stored_type,
+                ..unit.manifest_entry()
+            },
+        );
+    }
+
+    /// Records the type of the object stored for `unit`, a testcase of a frozen version
+    pub fn record_frozen_stored_type(&mut self, unit: &GenerationUnit, stored_type: StoredType) {
+        self.frozen_stored_types
+            .insert(unit.manifest_key(), stored_type);
+    }
+
+    /// The type of the object stored for `unit`, if it was recorded
+    pub fn stored_type(&self, unit: &GenerationUnit) -> Option<StoredType> {
+        let key = unit.manifest_key();
+        self.testcases
+            .get(&key)
+            .and_then(|entry| entry.stored_type.clone())
+            .or_else(|| self.frozen_stored_types.get(&key).cloned())
+    }
+
+    /// Removes the entries that do not correspond to any of `units`
+    pub fn retain(&mut self, units: &[GenerationUnit]) {
+        self.testcases
+            .retain(|key, _| units.iter().any(|unit| unit.manifest_key() == *key));
+    }
+}
+
+/// Derives a seed from the global prng seed and a list of identifiers, using the 128 bits
+/// FNV-1a hash. The hash is implemented here to make sure that it will never change.
+fn derive_seed(seed: u128, identifiers: &[&[u8]]) -> u128 {
+    const FNV_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut hash_bytes = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u128;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    };
+
+    hash_bytes(&seed.to_le_bytes());
+    for identifier in identifiers {
+        // Prefix with the length to avoid collisions between ("ab", "c") and ("a", "bc")
+        hash_bytes(&identifier.len().to_le_bytes());
+        hash_bytes(identifier);
+    }
+
+    hash
+}
+
+/// Declaration of a testcase: its metadata, and how to generate the object that is tested.
+///
+/// The framework takes care of seeding the prng, storing the object in all the formats and
+/// emitting the metadata entry of the testcase.
+pub struct TestcaseDefinition {
+    pub metadata: TestMetadata,
+    /// Creates the tfhe-rs object of the testcase and versionizes it. The prng is seeded with the
+    /// seed of the testcase before this is called.
+    pub generate: fn(&GenerationUnit) -> VersionedData,
+    /// Tags added to the ones derived from the metadata (eg: [`SLOW_TAG`])
+    pub tags: Vec<&'static str>,
+}
+
+impl TestcaseDefinition {
+    pub fn new(metadata: TestMetadata, generate: fn(&GenerationUnit) -> VersionedData) -> Self {
+        Self {
+            metadata,
+            generate,
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn with_tags(self, tags: &[&'static str]) -> Self {
+        Self {
+            tags: tags.to_vec(),
+            ..self
+        }
+    }
+}
+
+/// A testcase that can be generated independently of the others
+#[derive(Clone)]
+pub struct GenerationUnit {
+    pub version: &'static str,
+    pub module: &'static str,
+    pub metadata: TestMetadata,
+    generate: fn(&GenerationUnit) -> VersionedData,
+    tags: Vec<&'static str>,
+    seed_prng: fn(u128),
+    /// The global prng seed, from which the seed of each testcase is derived
+    base_seed: u128,
+    data_dir: PathBuf,
+    tfhe_revision: String,
+    /// The inputs of the auxiliaries of this testcase
+    auxiliary_entries: BTreeMap<String, ManifestEntry>,
+}
+
+impl GenerationUnit {
+    pub fn test_filename(&self) -> String {
+        self.metadata.test_filename()
+    }
+
+    /// The seed used to generate this testcase
+    pub fn seed(&self) -> u128 {
+        self.seed_of(&self.test_filename())
+    }
+
+    /// The seed used to generate another file of the same module. This should be used to
+    /// regenerate the data this testcase depends on (eg: the key used to encrypt a ciphertext).
+    ///
+    /// The seed only depends on the global seed, the version, the module and the filename, so
+    /// adding or reordering testcases does not modify the data of the existing ones.
+    pub fn seed_of(&self, test_filename: &str) -> u128 {
+        derive_seed(
+            self.base_seed,
+            &[
+                self.version.as_bytes(),
+                self.module.as_bytes(),
+                test_filename.as_bytes(),
+            ],
+        )
+    }
+
+    /// Seeds the prng with the seed of `test_filename`, runs `f` and seeds the prng back with the
+    /// seed of this testcase. This should be used to regenerate the data this testcase depends on
+    /// (eg: the key used to encrypt a ciphertext).
+    pub fn regenerate<R, F: FnOnce() -> R>(&self, test_filename: &str, f: F) -> R {
+        (self.seed_prng)(self.seed_of(test_filename));
+        let result = f();
+        (self.seed_prng)(self.seed());
+        result
+    }
+
+    /// Directory where the files of the module of this testcase are stored
+    pub fn dir(&self) -> PathBuf {
+        self.data_dir.join(self.module)
+    }
+
+    /// Generates the data for this testcase and stores it in the version directory. Returns the
+    /// type of the stored object, if the generator recorded it.
+    ///
+    /// # Panics
+    /// Panics if the stored type does not match the metadata of the testcase (eg: an object
+    /// declared as compressed that is not)
+    pub fn generate(&self) -> Option<StoredType> {
+        fs::create_dir_all(self.dir()).unwrap();
+
+        let data = self.build();
+        data.store(self.dir(), &self.test_filename());
+        data.stored_type
+    }
+
+    /// Builds the object of this testcase without storing it, and returns its type. This is used
+    /// to record the type of the data of a frozen version, that cannot be generated again.
+    ///
+    /// # Panics
+    /// Panics if the type does not match the metadata of the testcase
+    pub fn build_stored_type(&self) -> Option<StoredType> {
+        self.build().stored_type
+    }
+
+    fn build(&self) -> VersionedData {
+        (self.seed_prng)(self.seed());
+        let data = (self.generate)(self);
+
+        if let Some(stored_type) = data.stored_type() {
+            if let Err(e) = self.metadata.check_stored_type(stored_type) {
+                panic!("Invalid testcase {}: {}", self.testcase().id(), e);
+            }
+        }
+
+        data
+    }
+
+    /// The files where the data of this testcase and its schema are stored
+    pub fn data_files(&self) -> Vec<PathBuf> {
+        DATA_EXTENSIONS
+            .iter()
+            .map(|extension| {
+                self.dir()
+                    .join(format!("{}.{}", self.test_filename(), extension))
+            })
+            .chain(std::iter::once(schema_path(
+                self.dir(),
+                &self.test_filename(),
+            )))
+            .collect()
+    }
+
+    fn manifest_key(&self) -> String {
+        format!("{}/{}", self.module, self.test_filename())
+    }
+
+    /// The inputs used to generate this testcase
+    pub fn manifest_entry(&self) -> ManifestEntry {
+        ManifestEntry {
+            metadata: ron::to_string(&self.metadata).unwrap(),
+            seed: self.seed(),
+            tfhe_revision: self.tfhe_revision.clone(),
+            auxiliaries: self.auxiliary_entries.clone(),
+            stored_type: None,
+        }
+    }
+
+    /// Returns true if this testcase uses the data of `other` (eg: a ciphertext encrypted with
+    /// the key of `other`)
+    pub fn uses(&self, other: &GenerationUnit) -> bool {
+        self.version == other.version
+            && self.module == other.module
+            && self
+                .metadata
+                .auxiliary_filenames()
+                .contains(&other.test_filename())
+    }
+
+    /// The tags of this testcase: its module, the kind of object it holds, its speed and the
+    /// tags given in its definition. Testcases that are not tagged as slow are tagged as fast.
+    pub fn tags(&self) -> BTreeSet<String> {
+        let mut tags: BTreeSet<String> = [self.module, self.metadata.kind_tag()]
+            .into_iter()
+            .chain(self.tags.iter().copied())
+            .map(str::to_string)
+            .collect();
+
+        if !tags.contains(SLOW_TAG) {
+            tags.insert(FAST_TAG.to_string());
+        }
+
+        tags
+    }
+
+    /// The metadata of this testcase
+    pub fn testcase(&self) -> Testcase {
+        Testcase {
+            tfhe_version_min: self.version.to_string(),
+            tfhe_module: self.module.to_string(),
+            metadata: self.metadata.clone(),
+            version_tags: None,
+            stored_type: None,
+            tags: self.tags(),
+        }
+    }
+
+    /// The testcase, with the version tags read from its stored cbor data and the stored type
+    /// recorded in the `manifest`. They are missing if the data have not been generated.
+    ///
+    /// Returns an error if the stored cbor data cannot be read (eg: they were not fetched with
+    /// git-lfs), so that the recorded tags are never dropped.
+    pub fn generated_testcase(&self, manifest: &GenerationManifest) -> Result<Testcase, String> {
+        let cbor_path = self.dir().join(format!("{}.cbor", self.test_filename()));
+        let version_tags = if cbor_path.exists() {
+            stored_version_tags(cbor_path)?
+        } else {
+            None
+        };
+
+        Ok(Testcase {
+            version_tags,
+            stored_type: manifest.stored_type(self),
+            ..self.testcase()
+        })
+    }
+}
+
+/// The inputs of the auxiliaries of `unit`, found in `units`
+fn auxiliary_entries(
+    units: &[GenerationUnit],
+    unit: &GenerationUnit,
+) -> BTreeMap<String, ManifestEntry> {
+    units
+        .iter()
+        .filter(|auxiliary| unit.uses(auxiliary))
+        .map(|auxiliary| {
+            let entry = ManifestEntry {
+                auxiliaries: auxiliary_entries(units, auxiliary),
+                ..auxiliary.manifest_entry()
+            };
+            (auxiliary.test_filename(), entry)
+        })
+        .collect()
+}
+
+/// Adds to `outdated` the units that use the data of an outdated unit, directly or through
+/// another auxiliary, since their data are not valid anymore once it is generated again
+fn with_dependent_units(units: &[GenerationUnit], outdated: &mut Vec<GenerationUnit>) {
+    loop {
+        let dependents: Vec<GenerationUnit> = units
+            .iter()
+            .filter(|unit| {
+                outdated
+                    .iter()
+                    .all(|outdated_unit| outdated_unit.manifest_key() != unit.manifest_key())
+                    && outdated
+                        .iter()
+                        .any(|outdated_unit| unit.uses(outdated_unit))
+            })
+            .cloned()
+            .collect();
+
+        if dependents.is_empty() {
+            return;
+        }
+        outdated.extend(dependents);
+    }
+}
+
+/// Checks that none of the selected `units` of a frozen version would have to be generated: their
+/// data must all be stored, and `force` must not be set.
+fn check_frozen_units(units: &[&GenerationUnit], force: bool) -> Result<(), String> {
+    if force && !units.is_empty() {
+        return Err("--force cannot be used on a frozen version".to_string());
+    }
+
+    let mut errors = Vec::new();
+    for unit in units {
+        let is_stored = DATA_EXTENSIONS.iter().all(|extension| {
+            unit.dir()
+                .join(format!("{}.{}", unit.test_filename(), extension))
+                .is_file()
+        });
+
+        if !is_stored {
+            errors.push(format!(
+                "Testcase {} has no stored data, it should be added to a newer version",
+                unit.testcase().id()
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Records the types of the stored objects of the `units` of a frozen version, for the testcases
+/// whose type is missing from the manifest. The objects are built again but not stored, since
+/// their data cannot be generated again. Returns the number of recorded types.
+fn record_frozen_stored_types(
+    units: &[&GenerationUnit],
+    manifest: &mut GenerationManifest,
+    workers: usize,
+) -> usize {
+    let missing: Vec<GenerationUnit> = units
+        .iter()
+        .filter(|unit| manifest.stored_type(unit).is_none())
+        .map(|unit| (*unit).clone())
+        .collect();
+    let stored_types = parallel_map(&missing, workers, GenerationUnit::build_stored_type);
+
+    let mut recorded = 0;
+    for (unit, stored_type) in missing.iter().zip(stored_types) {
+        if let Some(stored_type) = stored_type {
+            manifest.record_frozen_stored_type(unit, stored_type);
+            recorded += 1;
+        }
+    }
+    recorded
+}
+
+/// Generates all the `units`, using `workers` threads. If `workers` is 0, the number of threads
+/// is given by [`std::thread::available_parallelism`]. Returns the types of the stored objects,
+/// in the order of `units`.
+pub fn generate_units(units: &[GenerationUnit], workers: usize) -> Vec<Option<StoredType>> {
+    parallel_map(units, workers, GenerationUnit::generate)
+}
+
 pub trait TfhersVersion {
     const VERSION_NUMBER: &'static str;
 
+    /// The data of a frozen version were generated once and can never be generated again, for
+    /// example because they were generated before each testcase had its own seed. The generator
+    /// of a frozen version only records the types of the stored objects and writes the metadata
+    /// of its testcases, and fails if a testcase would have to be generated. Its manifest only
+    /// holds the stored types, since the inputs that produced its data are not known.
+    const FROZEN: bool = false;
+
     fn data_dir() -> PathBuf {
         let base_data_dir = data_dir(env!("CARGO_MANIFEST_DIR"));
         dir_for_version(base_data_dir, Self::VERSION_NUMBER)
     }
 
-    /// How to fix the prng seed for this version to make sure the generated testcases do not change every time we run the script
+    /// The exact tfhe-rs dependency used to generate the data, recorded in the generation
+    /// manifest so that the data are generated again if it changes. The generator crate should
+    /// pin it to a git revision or an exact version. It is not recorded for a frozen version, whose
+    /// data may have been generated with another revision.
+    fn tfhe_revision() -> Result<String, String> {
+        locked_dependency_revision("tfhe", Self::VERSION_NUMBER)
+    }
+
+    /// How to fix the prng seed for this version to make sure the generated testcases do not change every time we run the script.
+    /// This is called with the seed of a testcase before it is generated, so that the data of a
+    /// testcase does not depend on the other ones. If a testcase needs data from another one
+    /// (eg: a key), it should regenerate it with [`GenerationUnit::regenerate`].
     fn seed_prng(seed: u128);
 
-    /// Generates data for the "shortint" module for this version.
-    /// This should create tfhe-rs shortint types, versionize them and store them into the version specific directory.
-    /// The metadata for the generated tests should be returned in the same order that the tests will be run.
-    fn gen_shortint_data() -> Vec<TestMetadata>;
+    /// The testcases of this version, for all the modules.
+    /// They should be returned in the same order that the tests will be run.
+    fn testcases() -> Vec<TestcaseDefinition>;
+
+    /// All the testcases of this version, as units that can be generated independently.
+    /// Each unit has its own seed, derived from `seed`.
+    fn generation_units(seed: u128) -> Result<Vec<GenerationUnit>, String> {
+        let tfhe_revision = Self::tfhe_revision()?;
+
+        let mut units: Vec<GenerationUnit> = Self::testcases()
+            .into_iter()
+            .map(|definition| {
+                let module = definition.metadata.module().ok_or_else(|| {
+                    format!(
+                        "Testcase {} of tfhe-rs {} has an unsupported kind",
+                        definition.metadata.test_filename(),
+                        Self::VERSION_NUMBER
+                    )
+                })?;
+
+                Ok(GenerationUnit {
+                    version: Self::VERSION_NUMBER,
+                    module,
+                    metadata: definition.metadata,
+                    generate: definition.generate,
+                    tags: definition.tags,
+                    seed_prng: Self::seed_prng,
+                    base_seed: seed,
+                    data_dir: Self::data_dir(),
+                    tfhe_revision: tfhe_revision.clone(),
+                    auxiliary_entries: BTreeMap::new(),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        let auxiliary_entries: Vec<_> = units
+            .iter()
+            .map(|unit| auxiliary_entries(&units, unit))
+            .collect();
+        for (unit, entries) in units.iter_mut().zip(auxiliary_entries) {
+            unit.auxiliary_entries = entries;
+        }
+
+        Ok(units)
+    }
+}
+
+/// Selection of the testcases that should be (re)generated, parsed from the command line
+#[derive(Clone, Debug, Default)]
+pub struct GenerationArgs {
+    pub jobs: usize,
+    pub version: Option<String>,
+    pub module: Option<String>,
+    pub test_filename: Option<String>,
+    pub force: bool,
+    /// Where the metadata of all the testcases of a version should be written
+    pub metadata_output: Option<PathBuf>,
+    pub help: bool,
+}
+
+impl GenerationArgs {
+    pub const USAGE: &'static str = "\
+Options:
+    --jobs <N>                Number of testcases generated in parallel (default: number of cpus)
+    --version <VERSION>       Only generate the data for this tfhe-rs version (eg: 0.6)
+    --module <MODULE>         Only generate the data for this module (eg: high_level_api)
+    --test <TEST_FILENAME>    Only generate the testcase stored in this file, without extension
+    --force                   Generate the selected testcases even if they are up to date
+    --metadata-output <PATH>  Write the metadata of all the testcases of the version in this file
+    --help                    Print this message";
+
+    pub fn parse<I: IntoIterator<Item = String>>(cli_args: I) -> Result<Self, String> {
+        let mut args = Self::default();
+        let mut cli_args = cli_args.into_iter();
+
+        while let Some(arg) = cli_args.next() {
+            let mut value = || {
+                cli_args
+                    .next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+
+            match arg.as_str() {
+                "--jobs" => {
+                    args.jobs = value()?
+                        .parse()
+                        .map_err(|e| format!("Invalid number of jobs: {}", e))?
+                }
+                "--version" => args.version = Some(value()?),
+                "--module" => args.module = Some(value()?),
+                "--test" => args.test_filename = Some(value()?),
+                "--force" => args.force = true,
+                "--metadata-output" => args.metadata_output = Some(PathBuf::from(value()?)),
+                "--help" => args.help = true,
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        Ok(args)
+    }
+
+    /// Converts back the arguments into command line arguments, to forward them to a generator
+    pub fn to_cli_args(&self) -> Vec<String> {
+        let mut cli_args = vec!["--jobs".to_string(), self.jobs.to_string()];
+
+        let options = [
+            ("--version", &self.version),
+            ("--module", &self.module),
+            ("--test", &self.test_filename),
+        ];
+        for (name, value) in options {
+            if let Some(value) = value {
+                cli_args.extend([name.to_string(), value.clone()]);
+            }
+        }
+
+        if self.force {
+            cli_args.push("--force".to_string());
+        }
+
+        if let Some(path) = &self.metadata_output {
+            cli_args.extend(["--metadata-output".to_string(), path.display().to_string()]);
+        }
+
+        cli_args
+    }
+
+    pub fn selects_version(&self, version: &str) -> bool {
+        self.version.as_deref().is_none_or(|v| v == version)
+    }
+
+    pub fn selects(&self, unit: &GenerationUnit) -> bool {
+        let matches =
+            |filter: &Option<String>, value: &str| filter.as_deref().is_none_or(|f| f == value);
+
+        self.selects_version(unit.version)
+            && matches(&self.module, unit.module)
+            && matches(&self.test_filename, &unit.test_filename())
+    }
+}
+
+/// Entry point of the generator crate of a tfhe-rs version: generates the data for the testcases
+/// selected on the command line that are not up to date, and writes the metadata of all the
+/// testcases of the version to the file given by `--metadata-output`.
+pub fn generate_version<Vers: TfhersVersion>() {
+    let args = GenerationArgs::parse(env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}", e, GenerationArgs::USAGE);
+        exit(1)
+    });
+
+    if args.help {
+        println!("{}", GenerationArgs::USAGE);
+        return;
+    }
+
+    let units = Vers::generation_units(PRNG_SEED).unwrap_or_else(|e| {
+        eprintln!(
+            "Failed to find the tfhe-rs {} dependency: {}",
+            Vers::VERSION_NUMBER,
+            e
+        );
+        exit(1)
+    });
+
+    // Two testcases stored in the same file would overwrite each other's data, and the keys
+    // used by a testcase should be declared to be generated
+    let testcases: Vec<Testcase> = units.iter().map(GenerationUnit::testcase).collect();
+    if let Err(e) = check_unique_testcases(&testcases).and_then(|_| check_references(&testcases)) {
+        eprintln!(
+            "Invalid testcases for tfhe-rs {}:\n{}",
+            Vers::VERSION_NUMBER,
+            e
+        );
+        exit(1)
+    }
 
-    /// Generates data for the "high_level_api" module for this version.
-    /// This should create tfhe-rs HL types, versionize them and store them into the version specific directory.
-    /// The metadata for the generated tests should be returned in the same order that the tests will be run.
-    fn gen_hl_data() -> Vec<TestMetadata>;
+    let selected_units: Vec<&GenerationUnit> =
+        units.iter().filter(|unit| args.selects(unit)).collect();
+
+    if selected_units.is_empty() {
+        println!(
+            "No testcase of tfhe-rs {} matches the selection",
+            Vers::VERSION_NUMBER
+        );
+    }
+
+    // The manifest lists the inputs used to generate the testcases of this version
+    let manifest_path = Vers::data_dir().join(MANIFEST_FILENAME);
+    let mut manifest = GenerationManifest::load(&manifest_path).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(1)
+    });
+
+    if Vers::FROZEN {
+        if let Err(e) = check_frozen_units(&selected_units, args.force) {
+            eprintln!(
+                "The data of tfhe-rs {} are frozen and can never be generated again:\n{}",
+                Vers::VERSION_NUMBER,
+                e
+            );
+            exit(1)
+        }
+
+        // The inputs of the units (their seed and tfhe-rs revision) are not the ones that
+        // produced the frozen data, so they are never recorded
+        let has_inputs = !manifest.testcases.is_empty();
+        manifest.testcases.clear();
+
+        let recorded = record_frozen_stored_types(&selected_units, &mut manifest, args.jobs);
+        if recorded > 0 || has_inputs {
+            println!(
+                "Recorded the stored type of {} testcase(s) of tfhe-rs {}",
+                recorded,
+                Vers::VERSION_NUMBER
+            );
+            manifest.store(&manifest_path);
+        }
+    } else {
+        // Testcases whose inputs did not change since the last generation are not generated
+        // again, unless they use data that are generated again
+        let mut outdated_units: Vec<GenerationUnit> = selected_units
+            .into_iter()
+            .filter(|unit| args.force || !manifest.is_up_to_date(unit))
+            .cloned()
+            .collect();
+        with_dependent_units(&units, &mut outdated_units);
+
+        println!(
+            "Generating {} testcase(s) for tfhe-rs {}, the other selected ones are up to date",
+            outdated_units.len(),
+            Vers::VERSION_NUMBER
+        );
+        let stored_types = generate_units(&outdated_units, args.jobs);
+
+        for (unit, stored_type) in outdated_units.iter().zip(stored_types) {
+            manifest.update(unit, stored_type);
+        }
+        manifest.retain(&units);
+        manifest.store(&manifest_path);
+    }
+
+    // The metadata is always written for all the testcases, with the version tags and the types
+    // of the data that are already stored for the testcases that were not generated again
+    if let Some(path) = &args.metadata_output {
+        let testcases: Vec<Testcase> = units
+            .iter()
+            .map(|unit| unit.generated_testcase(&manifest))
+            .collect::<Result<_, _>>()
+            .unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                exit(1)
+            });
+        store_metadata(&MetadataFile::new(&testcases), path);
+    }
 }
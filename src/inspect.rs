@@ -0,0 +1,799 @@
+use std::{
+    fmt::{self, Display},
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+use bincode::{DefaultOptions, Options};
+use serde::{
+    de::{self, DeserializeOwned, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    ser::{self, Serialize},
+    Deserialize, Deserializer,
+};
+
+/// The first bytes of the pointer files that git-lfs leaves in place of the data when they have
+/// not been fetched
+pub const LFS_POINTER_HEADER: &[u8] = b"version https://git-lfs";
+
+/// Number of items of a sequence that are kept in the tree, the other ones are only counted
+pub const SEQ_PREVIEW_LEN: usize = 3;
+
+/// The structure of a serialized value.
+///
+/// This only holds what can be retrieved from a cbor file, so that the trees built from a cbor
+/// file and from a rust value can be compared.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InspectNode {
+    /// A value of a versioned enum, with its version tag (eg: `V0`)
+    Versioned {
+        tag: String,
+        value: Box<InspectNode>,
+    },
+    /// A struct, a map or an enum variant, with its fields in the serialization order
+    Map(Vec<(String, InspectNode)>),
+    /// A sequence of `len` items, of which only the first ones are kept
+    Seq {
+        len: usize,
+        items: Vec<InspectNode>,
+    },
+    Bytes {
+        len: usize,
+    },
+    Integer(i128),
+    Float(f64),
+    Bool(bool),
+    Text(String),
+    Null,
+}
+
+/// Versionize dispatch enums have one variant per version, named `V0`, `V1`, ...
+pub(crate) fn is_version_tag(name: &str) -> bool {
+    name.strip_prefix('V')
+        .is_some_and(|index| !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl InspectNode {
+    /// Builds the node of an enum variant that holds a value
+    fn variant(variant: &str, value: InspectNode) -> Self {
+        if is_version_tag(variant) {
+            Self::Versioned {
+                tag: variant.to_string(),
+                value: Box::new(value),
+            }
+        } else {
+            Self::Map(vec![(variant.to_string(), value)])
+        }
+    }
+
+    /// The name used for this node when it is the key of a map
+    fn key_name(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            other => {
+                let mut key = String::new();
+                other.render(0, &mut key);
+                key
+            }
+        }
+    }
+
+    fn is_scalar(&self) -> bool {
+        !matches!(
+            self,
+            Self::Versioned { .. } | Self::Map(_) | Self::Seq { .. }
+        )
+    }
+
+    /// Writes this node at the end of the current line, and its children on the next lines,
+    /// indented by `indent` levels
+    fn render(&self, indent: usize, out: &mut String) {
+        let child_indent = "  ".repeat(indent + 1);
+
+        match self {
+            Self::Versioned { tag, value } => {
+                out.push_str(tag);
+                if value.is_scalar() {
+                    out.push(' ');
+                }
+                value.render(indent, out);
+            }
+            Self::Map(fields) => {
+                for (name, value) in fields {
+                    out.push('\n');
+                    out.push_str(&child_indent);
+                    out.push_str(name);
+                    out.push(':');
+                    if value.is_scalar() || matches!(value, Self::Versioned { .. }) {
+                        out.push(' ');
+                    }
+                    value.render(indent + 1, out);
+                }
+            }
+            Self::Seq { len, items } => {
+                out.push_str(&format!(" [{} items]", len));
+                if items.iter().all(Self::is_scalar) {
+                    let preview: Vec<String> = items.iter().map(Self::key_name).collect();
+                    let elided = if *len > items.len() { ", ..." } else { "" };
+                    out.push_str(&format!(" [{}{}]", preview.join(", "), elided));
+                } else {
+                    for item in items {
+                        out.push('\n');
+                        out.push_str(&child_indent);
+                        out.push('-');
+                        if item.is_scalar() || matches!(item, Self::Versioned { .. }) {
+                            out.push(' ');
+                        }
+                        item.render(indent + 1, out);
+                    }
+                    if *len > items.len() {
+                        out.push('\n');
+                        out.push_str(&child_indent);
+                        out.push_str(&format!("- ... ({} more)", len - items.len()));
+                    }
+                }
+            }
+            Self::Bytes { len } => out.push_str(&format!("<{} bytes>", len)),
+            Self::Integer(value) => out.push_str(&value.to_string()),
+            Self::Float(value) => out.push_str(&value.to_string()),
+            Self::Bool(value) => out.push_str(&value.to_string()),
+            Self::Text(text) => out.push_str(&format!("{:?}", text)),
+            Self::Null => out.push_str("null"),
+        }
+    }
+}
+
+impl Display for InspectNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        self.render(0, &mut out);
+        write!(f, "{}", out.trim_start())
+    }
+}
+
+struct InspectVisitor;
+
+impl<'de> Visitor<'de> for InspectVisitor {
+    type Value = InspectNode;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any self-describing value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(InspectNode::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(InspectNode::Integer(v.into()))
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<Self::Value, E> {
+        Ok(InspectNode::Integer(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(InspectNode::Integer(v.into()))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<Self::Value, E> {
+        i128::try_from(v)
+            .map(InspectNode::Integer)
+            .or_else(|_| Ok(InspectNode::Text(v.to_string())))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(InspectNode::Float(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(InspectNode::Text(v.to_string()))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(InspectNode::Bytes { len: v.len() })
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(InspectNode::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        InspectNode::deserialize(deserializer)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(InspectNode::Null)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        InspectNode::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut items = Vec::new();
+        let mut len = 0;
+
+        while len < SEQ_PREVIEW_LEN {
+            match seq.next_element()? {
+                Some(item) => items.push(item),
+                None => return Ok(InspectNode::Seq { len, items }),
+            }
+            len += 1;
+        }
+
+        // Big arrays are only counted
+        while seq.next_element::<IgnoredAny>()?.is_some() {
+            len += 1;
+        }
+
+        Ok(InspectNode::Seq { len, items })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut fields = Vec::new();
+
+        while let Some((key, value)) = map.next_entry::<InspectNode, InspectNode>()? {
+            fields.push((key.key_name(), value));
+        }
+
+        // Enums are serialized as a map with a single entry, the name of the variant
+        if let [(name, _)] = fields.as_slice() {
+            if is_version_tag(name) {
+                let (tag, value) = fields.pop().unwrap();
+                return Ok(InspectNode::variant(&tag, value));
+            }
+        }
+
+        Ok(InspectNode::Map(fields))
+    }
+}
+
+impl<'de> Deserialize<'de> for InspectNode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(InspectVisitor)
+    }
+}
+
+/// Error returned by the [`InspectSerializer`]
+#[derive(Debug)]
+pub struct InspectError(String);
+
+impl Display for InspectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for InspectError {}
+
+impl ser::Error for InspectError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+/// A serializer that builds the [`InspectNode`] tree of a value instead of encoding it
+pub struct InspectSerializer;
+
+/// Builds the items of a sequence, keeping only the first ones
+pub struct SeqBuilder {
+    len: usize,
+    items: Vec<InspectNode>,
+    /// For tuple variants, the name of the variant that holds the sequence
+    variant: Option<&'static str>,
+}
+
+impl SeqBuilder {
+    fn new(variant: Option<&'static str>) -> Self {
+        Self {
+            len: 0,
+            items: Vec::new(),
+            variant,
+        }
+    }
+
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), InspectError> {
+        if self.len < SEQ_PREVIEW_LEN {
+            self.items.push(value.serialize(InspectSerializer)?);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    fn build(self) -> InspectNode {
+        let seq = InspectNode::Seq {
+            len: self.len,
+            items: self.items,
+        };
+
+        match self.variant {
+            Some(variant) => InspectNode::variant(variant, seq),
+            None => seq,
+        }
+    }
+}
+
+/// Builds the fields of a struct or a map
+pub struct MapBuilder {
+    fields: Vec<(String, InspectNode)>,
+    next_key: Option<String>,
+    /// For struct variants, the name of the variant that holds the fields
+    variant: Option<&'static str>,
+}
+
+impl MapBuilder {
+    fn new(variant: Option<&'static str>) -> Self {
+        Self {
+            fields: Vec::new(),
+            next_key: None,
+            variant,
+        }
+    }
+
+    fn build(self) -> InspectNode {
+        let map = InspectNode::Map(self.fields);
+
+        match self.variant {
+            Some(variant) => InspectNode::variant(variant, map),
+            None => map,
+        }
+    }
+}
+
+impl ser::Serializer for InspectSerializer {
+    type Ok = InspectNode;
+    type Error = InspectError;
+
+    type SerializeSeq = SeqBuilder;
+    type SerializeTuple = SeqBuilder;
+    type SerializeTupleStruct = SeqBuilder;
+    type SerializeTupleVariant = SeqBuilder;
+    type SerializeMap = MapBuilder;
+    type SerializeStruct = MapBuilder;
+    type SerializeStructVariant = MapBuilder;
+
+    fn serialize_bool(self, v: bool) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Integer(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Integer(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Integer(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Integer(v.into()))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Integer(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Integer(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Integer(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Integer(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Integer(v.into()))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<InspectNode, InspectError> {
+        Ok(i128::try_from(v)
+            .map(InspectNode::Integer)
+            .unwrap_or_else(|_| InspectNode::Text(v.to_string())))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Float(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Text(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Text(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Bytes { len: v.len() })
+    }
+
+    fn serialize_none(self) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<InspectNode, InspectError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::Text(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<InspectNode, InspectError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<InspectNode, InspectError> {
+        Ok(InspectNode::variant(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqBuilder, InspectError> {
+        Ok(SeqBuilder::new(None))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<SeqBuilder, InspectError> {
+        Ok(SeqBuilder::new(None))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<SeqBuilder, InspectError> {
+        Ok(SeqBuilder::new(None))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SeqBuilder, InspectError> {
+        Ok(SeqBuilder::new(Some(variant)))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapBuilder, InspectError> {
+        Ok(MapBuilder::new(None))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<MapBuilder, InspectError> {
+        Ok(MapBuilder::new(None))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<MapBuilder, InspectError> {
+        Ok(MapBuilder::new(Some(variant)))
+    }
+}
+
+impl ser::SerializeSeq for SeqBuilder {
+    type Ok = InspectNode;
+    type Error = InspectError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), InspectError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<InspectNode, InspectError> {
+        Ok(self.build())
+    }
+}
+
+impl ser::SerializeTuple for SeqBuilder {
+    type Ok = InspectNode;
+    type Error = InspectError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), InspectError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<InspectNode, InspectError> {
+        Ok(self.build())
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqBuilder {
+    type Ok = InspectNode;
+    type Error = InspectError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), InspectError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<InspectNode, InspectError> {
+        Ok(self.build())
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqBuilder {
+    type Ok = InspectNode;
+    type Error = InspectError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), InspectError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<InspectNode, InspectError> {
+        Ok(self.build())
+    }
+}
+
+impl ser::SerializeMap for MapBuilder {
+    type Ok = InspectNode;
+    type Error = InspectError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), InspectError> {
+        self.next_key = Some(key.serialize(InspectSerializer)?.key_name());
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), InspectError> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| InspectError("Map value serialized before its key".to_string()))?;
+        self.fields.push((key, value.serialize(InspectSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<InspectNode, InspectError> {
+        Ok(self.build())
+    }
+}
+
+impl ser::SerializeStruct for MapBuilder {
+    type Ok = InspectNode;
+    type Error = InspectError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), InspectError> {
+        self.fields
+            .push((key.to_string(), value.serialize(InspectSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<InspectNode, InspectError> {
+        Ok(self.build())
+    }
+}
+
+impl ser::SerializeStructVariant for MapBuilder {
+    type Ok = InspectNode;
+    type Error = InspectError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), InspectError> {
+        self.fields
+            .push((key.to_string(), value.serialize(InspectSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<InspectNode, InspectError> {
+        Ok(self.build())
+    }
+}
+
+/// Builds the structure of a cbor encoded value
+pub fn inspect_cbor<R: Read>(reader: R) -> Result<InspectNode, String> {
+    ciborium::de::from_reader(reader).map_err(|e| format!("Failed to parse cbor data: {}", e))
+}
+
+/// Builds the structure of a cbor file
+pub fn inspect_cbor_file<P: AsRef<Path>>(path: P) -> Result<InspectNode, String> {
+    let path = path.as_ref();
+    let file =
+        File::open(path).map_err(|e| format!("Failed to read file {}: {}", path.display(), e))?;
+    let mut reader = BufReader::new(file);
+
+    let header = reader
+        .fill_buf()
+        .map_err(|e| format!("Failed to read file {}: {}", path.display(), e))?;
+    if header.starts_with(LFS_POINTER_HEADER) {
+        return Err(format!(
+            "{} is a git-lfs pointer and not the data, fetch the data with `git lfs pull`",
+            path.display()
+        ));
+    }
+
+    inspect_cbor(reader).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Builds the structure of a value, as it would be seen in a cbor file
+pub fn inspect_value<T: Serialize>(value: &T) -> Result<InspectNode, String> {
+    value
+        .serialize(InspectSerializer)
+        .map_err(|e| format!("Failed to inspect value: {}", e))
+}
+
+/// Builds the structure of a bincode file. Since bincode is not self-describing, the data are
+/// first deserialized as `T`, that should be the versioned type stored in the file (eg: the
+/// `Versioned` type of `FheUint8`).
+pub fn inspect_bincode<T: Serialize + DeserializeOwned, P: AsRef<Path>>(
+    path: P,
+) -> Result<InspectNode, String> {
+    let path = path.as_ref();
+    let file =
+        File::open(path).map_err(|e| format!("Failed to read file {}: {}", path.display(), e))?;
+
+    let options = DefaultOptions::new().with_fixint_encoding();
+    let value: T = options
+        .deserialize_from(file)
+        .map_err(|e| format!("Failed to parse file {}: {}", path.display(), e))?;
+
+    inspect_value(&value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde::Serialize;
+
+    use super::*;
+    use crate::test_fixtures::temp_dir;
+
+    #[derive(Serialize)]
+    enum Versioned<T> {
+        #[allow(dead_code)]
+        V0(T),
+        V1(T),
+    }
+
+    #[derive(Serialize)]
+    enum Kind {
+        Big,
+    }
+
+    #[derive(Serialize)]
+    struct Key {
+        name: String,
+        data: Vec<u64>,
+        kind: Kind,
+        inner: Versioned<u8>,
+        missing: Option<u8>,
+    }
+
+    fn key() -> Versioned<Key> {
+        Versioned::V1(Key {
+            name: "key".to_string(),
+            data: vec![10, 11, 12, 13, 14],
+            kind: Kind::Big,
+            inner: Versioned::V0(3),
+            missing: None,
+        })
+    }
+
+    #[test]
+    fn cbor_structure_is_inspected() {
+        let mut serialized = Vec::new();
+        ciborium::ser::into_writer(&key(), &mut serialized).unwrap();
+
+        let expected = InspectNode::Versioned {
+            tag: "V1".to_string(),
+            value: Box::new(InspectNode::Map(vec![
+                ("name".to_string(), InspectNode::Text("key".to_string())),
+                (
+                    "data".to_string(),
+                    InspectNode::Seq {
+                        len: 5,
+                        items: vec![
+                            InspectNode::Integer(10),
+                            InspectNode::Integer(11),
+                            InspectNode::Integer(12),
+                        ],
+                    },
+                ),
+                ("kind".to_string(), InspectNode::Text("Big".to_string())),
+                (
+                    "inner".to_string(),
+                    InspectNode::Versioned {
+                        tag: "V0".to_string(),
+                        value: Box::new(InspectNode::Integer(3)),
+                    },
+                ),
+                ("missing".to_string(), InspectNode::Null),
+            ])),
+        };
+        assert_eq!(inspect_cbor(serialized.as_slice()), Ok(expected));
+    }
+
+    #[test]
+    fn cbor_and_values_have_the_same_structure() {
+        let mut serialized = Vec::new();
+        ciborium::ser::into_writer(&key(), &mut serialized).unwrap();
+
+        assert_eq!(inspect_cbor(serialized.as_slice()), inspect_value(&key()));
+    }
+
+    #[test]
+    fn structure_is_rendered() {
+        let rendered = inspect_value(&key()).unwrap().to_string();
+        assert_eq!(
+            rendered,
+            "V1\n  name: \"key\"\n  data: [5 items] [10, 11, 12, ...]\n  kind: \"Big\"\n  \
+            inner: V0 3\n  missing: null"
+        );
+    }
+
+    #[test]
+    fn integers_beyond_i128_are_kept_as_text() {
+        let mut serialized = Vec::new();
+        ciborium::ser::into_writer(&u128::MAX, &mut serialized).unwrap();
+
+        assert_eq!(
+            inspect_cbor(serialized.as_slice()),
+            Ok(InspectNode::Text(u128::MAX.to_string()))
+        );
+    }
+
+    #[test]
+    fn lfs_pointers_are_reported() {
+        let dir = temp_dir("inspect_lfs_pointer");
+        let path = dir.join("ct.cbor");
+        fs::write(
+            &path,
+            "version https://git-lfs.github.com/spec/v1\noid sha256:e5e9\nsize 74481\n",
+        )
+        .unwrap();
+
+        let result = inspect_cbor_file(&path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let error = result.unwrap_err();
+        assert!(error.contains("git-lfs pointer"), "{}", error);
+    }
+}
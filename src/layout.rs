@@ -0,0 +1,35 @@
+//! The layout of the data directory: where the metadata, the data and the schema snapshots of the
+//! testcases are stored.
+//!
+//! The build script includes this file to find the data to embed, so it should only use `std`.
+
+use std::path::{Path, PathBuf};
+
+pub const SHORTINT_MODULE_NAME: &str = "shortint";
+pub const HL_MODULE_NAME: &str = "high_level_api";
+
+/// The file in the data directory that holds the metadata of the testcases of each module
+pub const METADATA_FILES: [(&str, &str); 2] = [
+    (SHORTINT_MODULE_NAME, "shortint.ron"),
+    (HL_MODULE_NAME, "high_level_api.ron"),
+];
+
+/// Extensions of the files in which each versioned object is stored, one per format
+pub const DATA_EXTENSIONS: [&str; 2] = ["cbor", "bcode"];
+
+/// Extension of the schema snapshot stored next to the data of each testcase
+pub const SCHEMA_EXTENSION: &str = "schema.ron";
+
+pub fn dir_for_version<P: AsRef<Path>>(data_dir: P, version: &str) -> PathBuf {
+    let mut path = data_dir.as_ref().to_path_buf();
+    path.push(version.replace('.', "_"));
+
+    path
+}
+
+/// Returns the path of the schema snapshot of a file stored in `dir`, `filename` being given
+/// without extension
+pub fn schema_path<P: AsRef<Path>>(dir: P, filename: &str) -> PathBuf {
+    dir.as_ref()
+        .join(format!("{}.{}", filename, SCHEMA_EXTENSION))
+}
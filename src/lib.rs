@@ -1,7 +1,15 @@
 use core::f64;
 use std::{
     borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+    num::NonZeroUsize,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
 };
 
 #[cfg(feature = "load")]
@@ -10,25 +18,44 @@ use semver::{Version, VersionReq};
 use std::fmt::Display;
 use strum::Display;
 
+#[cfg(feature = "generate")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[cfg(feature = "generate")]
-pub mod data_0_6;
+#[cfg(feature = "load")]
+pub mod corpus;
+pub mod diff;
+#[cfg(feature = "load")]
+mod filter;
 #[cfg(feature = "generate")]
 pub mod generate;
+pub mod inspect;
+mod layout;
 #[cfg(feature = "load")]
 pub mod load;
+pub mod metadata;
+pub mod schema;
+#[cfg(test)]
+mod test_fixtures;
+pub mod verify;
 
 const DATA_DIR: &str = "data";
 
-pub const SHORTINT_MODULE_NAME: &str = "shortint";
-pub const HL_MODULE_NAME: &str = "high_level_api";
+pub use layout::{
+    dir_for_version, DATA_EXTENSIONS, HL_MODULE_NAME, METADATA_FILES, SHORTINT_MODULE_NAME,
+};
+
+/// Name of the file, inside the directory of each version, that lists the inputs used to
+/// generate each testcase
+pub const MANIFEST_FILENAME: &str = "manifest.ron";
 
 /// This struct re-defines tfhe-rs parameter sets but this allows to be independant
 /// of changes made into the  ParameterSet of tfhe-rs. The idea here is to define a type
 /// that is able to carry the information of the used parameters without using any tfhe-rs
 /// types.
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct TestParameterSet {
     pub lwe_dimension: usize,
     pub glwe_dimension: usize,
@@ -47,13 +74,6 @@ pub struct TestParameterSet {
     pub encryption_key_choice: Cow<'static, str>,
 }
 
-pub fn dir_for_version<P: AsRef<Path>>(data_dir: P, version: &str) -> PathBuf {
-    let mut path = data_dir.as_ref().to_path_buf();
-    path.push(version.replace('.', "_"));
-
-    path
-}
-
 pub fn data_dir<P: AsRef<Path>>(root: P) -> PathBuf {
     let mut path = PathBuf::from(root.as_ref());
     path.push(DATA_DIR);
@@ -61,6 +81,274 @@ pub fn data_dir<P: AsRef<Path>>(root: P) -> PathBuf {
     path
 }
 
+/// Environment variable holding the path of the data directory, used by [`locate_data_dir`]
+pub const DATA_DIR_ENV_VAR: &str = "TFHE_BACKWARD_COMPAT_DATA_DIR";
+
+/// Checks if `dir` holds the metadata files of all the modules
+fn is_data_dir(dir: &Path) -> bool {
+    METADATA_FILES
+        .iter()
+        .all(|(_, filename)| dir.join(filename).is_file())
+}
+
+/// Finds the data directory at runtime, so that the crates that use the data do not need to know
+/// where this repository is checked out.
+///
+/// If the [`DATA_DIR_ENV_VAR`] environment variable is set, it should give the data directory.
+/// Otherwise, the data directory is searched in the current directory and the directory of the
+/// crate being built or tested (given by `CARGO_MANIFEST_DIR` at runtime), and in all their
+/// parents. A directory is the data directory if it holds the metadata files of all the modules,
+/// or if its `data` sub-directory does.
+///
+/// The error lists all the places that were searched.
+pub fn locate_data_dir() -> Result<PathBuf, String> {
+    let mut start_dirs = Vec::new();
+    if let Ok(current_dir) = std::env::current_dir() {
+        start_dirs.push(current_dir);
+    }
+    if let Some(manifest_dir) = std::env::var_os("CARGO_MANIFEST_DIR") {
+        start_dirs.push(PathBuf::from(manifest_dir));
+    }
+
+    find_data_dir(
+        std::env::var_os(DATA_DIR_ENV_VAR).map(PathBuf::from),
+        &start_dirs,
+    )
+}
+
+/// Same as [`locate_data_dir`], with the value of [`DATA_DIR_ENV_VAR`] given in `env_dir` and
+/// the directories where the search starts given in `start_dirs`
+fn find_data_dir(env_dir: Option<PathBuf>, start_dirs: &[PathBuf]) -> Result<PathBuf, String> {
+    if let Some(dir) = env_dir {
+        return if is_data_dir(&dir) {
+            Ok(dir)
+        } else {
+            Err(format!(
+                "{} is set to {}, which does not hold the metadata files",
+                DATA_DIR_ENV_VAR,
+                dir.display()
+            ))
+        };
+    }
+
+    let mut searched: Vec<PathBuf> = Vec::new();
+    for start_dir in start_dirs {
+        for dir in start_dir.ancestors() {
+            for candidate in [dir.to_path_buf(), data_dir(dir)] {
+                if searched.contains(&candidate) {
+                    continue;
+                }
+                if is_data_dir(&candidate) {
+                    return Ok(candidate);
+                }
+                searched.push(candidate);
+            }
+        }
+    }
+
+    let searched: Vec<String> = searched
+        .iter()
+        .map(|dir| format!("  {}", dir.display()))
+        .collect();
+    Err(format!(
+        "Data directory not found, {} is not set and none of these directories holds the \
+        metadata files:\n{}",
+        DATA_DIR_ENV_VAR,
+        searched.join("\n")
+    ))
+}
+
+/// Loads the testcases of all the modules from the metadata files in `data_dir`
+pub(crate) fn load_all_metadata(data_dir: &Path) -> Result<Vec<Testcase>, String> {
+    let mut testcases = Vec::new();
+
+    for (_, filename) in METADATA_FILES {
+        let path = data_dir.join(filename);
+        let serialized = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to load test metadata {}: {}", path.display(), e))?;
+        let module_testcases = metadata::parse_metadata(&serialized)
+            .map_err(|e| format!("Invalid test metadata {}: {}", path.display(), e))?;
+
+        testcases.extend(module_testcases);
+    }
+
+    Ok(testcases)
+}
+
+/// Applies `f` to all the `jobs` using `workers` threads, and returns the results in the order of
+/// the jobs. If `workers` is 0, the number of threads is given by
+/// [`thread::available_parallelism`].
+#[cfg_attr(not(any(feature = "generate", feature = "load")), allow(dead_code))]
+pub(crate) fn parallel_map<T, R, F>(jobs: &[T], workers: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = NonZeroUsize::new(workers)
+        .or_else(|| thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get)
+        .min(jobs.len());
+
+    if workers <= 1 {
+        return jobs.iter().map(f).collect();
+    }
+
+    let next_job = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new(jobs.iter().map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let job_id = next_job.fetch_add(1, Ordering::Relaxed);
+                let Some(job) = jobs.get(job_id) else {
+                    break;
+                };
+
+                let result = f(job);
+                results.lock().unwrap()[job_id] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("All the jobs should have been run"))
+        .collect()
+}
+
+/// A clear value encrypted in an integer ciphertext, with the width and signedness of the
+/// encrypted integer type.
+///
+/// The value is checked to be in the range of its type when it is created and when the
+/// metadata are loaded, so that the expected value cannot differ from the decrypted one.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+#[serde(try_from = "UncheckedClearValue")]
+pub struct ClearValue {
+    pub value: i128,
+    pub num_bits: u32,
+    pub signed: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UncheckedClearValue {
+    value: i128,
+    num_bits: u32,
+    signed: bool,
+}
+
+impl TryFrom<UncheckedClearValue> for ClearValue {
+    type Error = String;
+
+    fn try_from(unchecked: UncheckedClearValue) -> Result<Self, Self::Error> {
+        let clear = Self {
+            value: unchecked.value,
+            num_bits: unchecked.num_bits,
+            signed: unchecked.signed,
+        };
+
+        if clear.is_in_range() {
+            Ok(clear)
+        } else {
+            Err(format!("Clear value out of range: {}", clear))
+        }
+    }
+}
+
+impl ClearValue {
+    /// A value encrypted in an unsigned integer of `num_bits` bits.
+    /// Panics if the value does not fit, which is a compilation error in a const context.
+    pub const fn unsigned(value: u64, num_bits: u32) -> Self {
+        let clear = Self {
+            value: value as i128,
+            num_bits,
+            signed: false,
+        };
+        assert!(clear.is_in_range(), "Unsigned clear value out of range");
+        clear
+    }
+
+    /// A value encrypted in a signed integer of `num_bits` bits.
+    /// Panics if the value does not fit, which is a compilation error in a const context.
+    pub const fn signed(value: i64, num_bits: u32) -> Self {
+        let clear = Self {
+            value: value as i128,
+            num_bits,
+            signed: true,
+        };
+        assert!(clear.is_in_range(), "Signed clear value out of range");
+        clear
+    }
+
+    /// Checks that the value can be represented by the integer type
+    pub const fn is_in_range(&self) -> bool {
+        if self.num_bits == 0 || self.num_bits > 64 {
+            return false;
+        }
+
+        if self.signed {
+            let bound = 1i128 << (self.num_bits - 1);
+            -bound <= self.value && self.value < bound
+        } else {
+            0 <= self.value && self.value < (1i128 << self.num_bits)
+        }
+    }
+
+    /// Converts the value into the primitive type used to encrypt it, returning an error if
+    /// this type does not have the declared width and signedness
+    pub fn try_to_clear<T: ClearInteger>(&self) -> Result<T, String> {
+        if T::NUM_BITS != self.num_bits || T::SIGNED != self.signed {
+            return Err(format!(
+                "Clear value {} used with a {} integer of {} bits",
+                self,
+                if T::SIGNED { "signed" } else { "unsigned" },
+                T::NUM_BITS
+            ));
+        }
+
+        T::try_from(self.value).map_err(|_| format!("Clear value out of range: {}", self))
+    }
+
+    /// Converts the value into the primitive type used to encrypt it.
+    /// Panics if this type does not have the declared width and signedness.
+    pub fn to_clear<T: ClearInteger>(&self) -> T {
+        self.try_to_clear().unwrap()
+    }
+}
+
+impl fmt::Display for ClearValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = if self.signed { "i" } else { "u" };
+        write!(f, "{}{}{}", self.value, prefix, self.num_bits)
+    }
+}
+
+/// The primitive integer types in which a [`ClearValue`] can be converted
+pub trait ClearInteger: TryFrom<i128> {
+    const NUM_BITS: u32;
+    const SIGNED: bool;
+}
+
+macro_rules! impl_clear_integer {
+    ($($ty:ty => $signed:literal),*) => {
+        $(
+            impl ClearInteger for $ty {
+                const NUM_BITS: u32 = <$ty>::BITS;
+                const SIGNED: bool = $signed;
+            }
+        )*
+    };
+}
+
+impl_clear_integer!(
+    u8 => false, u16 => false, u32 => false, u64 => false,
+    i8 => true, i16 => true, i32 => true, i64 => true
+);
+
 pub trait TestType {
     /// The tfhe-rs module where this type reside
     fn module(&self) -> String;
@@ -95,6 +383,8 @@ pub trait TestType {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct ShortintClientKeyTest {
     pub test_filename: Cow<'static, str>,
     pub parameters: TestParameterSet,
@@ -115,6 +405,8 @@ impl TestType for ShortintClientKeyTest {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct ShortintCiphertextTest {
     pub test_filename: Cow<'static, str>,
     pub key_filename: Cow<'static, str>,
@@ -136,6 +428,8 @@ impl TestType for ShortintCiphertextTest {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct HlClientKeyTest {
     pub test_filename: Cow<'static, str>,
     pub parameters: TestParameterSet,
@@ -156,6 +450,8 @@ impl TestType for HlClientKeyTest {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct HlServerKeyTest {
     pub test_filename: Cow<'static, str>,
     pub client_key_filename: Cow<'static, str>,
@@ -177,6 +473,8 @@ impl TestType for HlServerKeyTest {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct HlPublicKeyTest {
     pub test_filename: Cow<'static, str>,
     pub client_key_filename: Cow<'static, str>,
@@ -199,12 +497,14 @@ impl TestType for HlPublicKeyTest {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct HlCiphertextTest {
     pub test_filename: Cow<'static, str>,
     pub key_filename: Cow<'static, str>,
     pub compressed: bool,
     pub compact: bool,
-    pub clear_value: u64,
+    pub clear_value: ClearValue,
 }
 
 impl TestType for HlCiphertextTest {
@@ -222,12 +522,14 @@ impl TestType for HlCiphertextTest {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct HlSignedCiphertextTest {
     pub test_filename: Cow<'static, str>,
     pub key_filename: Cow<'static, str>,
     pub compressed: bool,
     pub compact: bool,
-    pub clear_value: i64,
+    pub clear_value: ClearValue,
 }
 
 impl TestType for HlSignedCiphertextTest {
@@ -245,6 +547,8 @@ impl TestType for HlSignedCiphertextTest {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct HlBoolCiphertextTest {
     pub test_filename: Cow<'static, str>,
     pub key_filename: Cow<'static, str>,
@@ -268,10 +572,12 @@ impl TestType for HlBoolCiphertextTest {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct HlCiphertextListTest {
     pub test_filename: Cow<'static, str>,
     pub key_filename: Cow<'static, str>,
-    pub clear_values: Cow<'static, [u64]>,
+    pub clear_values: Cow<'static, [ClearValue]>,
 }
 
 impl TestType for HlCiphertextListTest {
@@ -289,10 +595,12 @@ impl TestType for HlCiphertextListTest {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct HlSignedCiphertextListTest {
     pub test_filename: Cow<'static, str>,
     pub key_filename: Cow<'static, str>,
-    pub clear_values: Cow<'static, [i64]>,
+    pub clear_values: Cow<'static, [ClearValue]>,
 }
 
 impl TestType for HlSignedCiphertextListTest {
@@ -310,6 +618,8 @@ impl TestType for HlSignedCiphertextListTest {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct HlBoolCiphertextListTest {
     pub test_filename: Cow<'static, str>,
     pub key_filename: Cow<'static, str>,
@@ -330,7 +640,20 @@ impl TestType for HlBoolCiphertextListTest {
     }
 }
 
+/// A testcase that this version of the crate cannot parse, eg: a kind of test that was added
+/// after it was released, or a test with a field it does not know. It is kept so that it can be
+/// reported as skipped.
+#[derive(Clone, Debug)]
+pub struct UnsupportedTest {
+    pub test_filename: Cow<'static, str>,
+    /// The kind of test, as written in the metadata (eg: `HlCiphertext`)
+    pub kind: String,
+    /// Why the testcase could not be parsed
+    pub reason: String,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Display)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
 pub enum TestMetadata {
     // Shortint
     ShortintCiphertext(ShortintCiphertextTest),
@@ -346,17 +669,443 @@ pub enum TestMetadata {
     HlClientKey(HlClientKeyTest),
     HlServerKey(HlServerKeyTest),
     HlPublicKey(HlPublicKeyTest),
+
+    /// Never read from or written to the metadata files, built by the parser of
+    /// [`metadata`](crate::metadata) for the testcases it does not support
+    #[serde(skip)]
+    Unsupported(UnsupportedTest),
+}
+
+impl TestMetadata {
+    /// The tfhe-rs module of the tested type, or `None` for the unsupported testcases, whose
+    /// module is only known from their [`Testcase`]
+    pub fn module(&self) -> Option<&'static str> {
+        match self {
+            Self::ShortintCiphertext(_) | Self::ShortintClientKey(_) => Some(SHORTINT_MODULE_NAME),
+            Self::HlCiphertext(_)
+            | Self::HlSignedCiphertext(_)
+            | Self::HlBoolCiphertext(_)
+            | Self::HlCiphertextList(_)
+            | Self::HlSignedCiphertextList(_)
+            | Self::HlBoolCiphertextList(_)
+            | Self::HlClientKey(_)
+            | Self::HlServerKey(_)
+            | Self::HlPublicKey(_) => Some(HL_MODULE_NAME),
+            Self::Unsupported(_) => None,
+        }
+    }
+
+    /// The names of the auxiliary files needed to run the test (eg: the key used to decrypt a
+    /// ciphertext), without path or extension
+    pub fn auxiliary_filenames(&self) -> Vec<String> {
+        match self {
+            Self::ShortintCiphertext(test) => vec![test.key_filename.to_string()],
+            Self::HlCiphertext(test) => vec![test.key_filename.to_string()],
+            Self::HlSignedCiphertext(test) => vec![test.key_filename.to_string()],
+            Self::HlBoolCiphertext(test) => vec![test.key_filename.to_string()],
+            Self::HlCiphertextList(test) => vec![test.key_filename.to_string()],
+            Self::HlSignedCiphertextList(test) => vec![test.key_filename.to_string()],
+            Self::HlBoolCiphertextList(test) => vec![test.key_filename.to_string()],
+            Self::HlServerKey(test) => vec![test.client_key_filename.to_string()],
+            Self::HlPublicKey(test) => vec![test.client_key_filename.to_string()],
+            Self::ShortintClientKey(_) | Self::HlClientKey(_) | Self::Unsupported(_) => Vec::new(),
+        }
+    }
+
+    /// The tag of the kind of object stored in the test file: [`KEY_TAG`] or [`CIPHERTEXT_TAG`],
+    /// or [`UNSUPPORTED_TAG`] if the kind of test is unknown
+    pub fn kind_tag(&self) -> &'static str {
+        match self {
+            Self::ShortintClientKey(_)
+            | Self::HlClientKey(_)
+            | Self::HlServerKey(_)
+            | Self::HlPublicKey(_) => KEY_TAG,
+            Self::ShortintCiphertext(_)
+            | Self::HlCiphertext(_)
+            | Self::HlSignedCiphertext(_)
+            | Self::HlBoolCiphertext(_)
+            | Self::HlCiphertextList(_)
+            | Self::HlSignedCiphertextList(_)
+            | Self::HlBoolCiphertextList(_) => CIPHERTEXT_TAG,
+            Self::Unsupported(_) => UNSUPPORTED_TAG,
+        }
+    }
+
+    /// Checks that the type of the stored object is consistent with the metadata: its name
+    /// should match the kind of the test and the `compressed` and `compact` flags, and its width
+    /// should match the clear values.
+    pub fn check_stored_type(&self, stored_type: &StoredType) -> Result<(), String> {
+        // The expected name, without the `Compressed` and `Compact` prefixes, and the flags of
+        // the metadata if they are declared
+        let (base_name, compressed, compact, num_bits) = match self {
+            Self::ShortintCiphertext(_) => ("Ciphertext", Some(false), Some(false), None),
+            Self::ShortintClientKey(_) => ("ClientKey", Some(false), Some(false), None),
+            Self::HlCiphertext(test) => (
+                "FheUint",
+                Some(test.compressed),
+                Some(test.compact),
+                Some(test.clear_value.num_bits),
+            ),
+            Self::HlSignedCiphertext(test) => (
+                "FheInt",
+                Some(test.compressed),
+                Some(test.compact),
+                Some(test.clear_value.num_bits),
+            ),
+            Self::HlBoolCiphertext(test) => {
+                ("FheBool", Some(test.compressed), Some(test.compact), None)
+            }
+            Self::HlCiphertextList(test) => (
+                "FheUintList",
+                None,
+                None,
+                test.clear_values.first().map(|clear| clear.num_bits),
+            ),
+            Self::HlSignedCiphertextList(test) => (
+                "FheIntList",
+                None,
+                None,
+                test.clear_values.first().map(|clear| clear.num_bits),
+            ),
+            Self::HlBoolCiphertextList(_) => ("FheBoolList", None, None, None),
+            Self::HlClientKey(_) => ("ClientKey", Some(false), Some(false), None),
+            Self::HlServerKey(test) => ("ServerKey", Some(test.compressed), Some(false), None),
+            Self::HlPublicKey(test) => {
+                ("PublicKey", Some(test.compressed), Some(test.compact), None)
+            }
+            Self::Unsupported(test) => {
+                return Err(format!(
+                    "The type of the unsupported test {} cannot be checked",
+                    test.kind
+                ))
+            }
+        };
+
+        let name = stored_type.short_name();
+        let (is_compressed, name) = match name.strip_prefix("Compressed") {
+            Some(rest) => (true, rest),
+            None => (false, name),
+        };
+        let (is_compact, name) = match name.strip_prefix("Compact") {
+            Some(rest) => (true, rest),
+            None => (false, name),
+        };
+
+        let mut errors = Vec::new();
+        if name != base_name {
+            errors.push(format!("expected a {}", base_name));
+        }
+        if compressed.is_some_and(|compressed| compressed != is_compressed) {
+            errors.push(format!(
+                "the metadata declares compressed: {}",
+                !is_compressed
+            ));
+        }
+        if compact.is_some_and(|compact| compact != is_compact) {
+            errors.push(format!("the metadata declares compact: {}", !is_compact));
+        }
+        if let (Some(expected), Some(stored)) = (num_bits, stored_type.num_bits) {
+            if expected != stored {
+                errors.push(format!(
+                    "the clear values have {} bits but the ciphertext has {}",
+                    expected, stored
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Stored type {} built with {} does not match the metadata of {}: {}",
+                stored_type.type_name,
+                stored_type.construction,
+                self,
+                errors.join(", ")
+            ))
+        }
+    }
+
+    /// The name of the file to be tested, without path or extension
+    pub fn test_filename(&self) -> String {
+        match self {
+            Self::ShortintCiphertext(test) => test.test_filename(),
+            Self::ShortintClientKey(test) => test.test_filename(),
+            Self::HlCiphertext(test) => test.test_filename(),
+            Self::HlSignedCiphertext(test) => test.test_filename(),
+            Self::HlBoolCiphertext(test) => test.test_filename(),
+            Self::HlCiphertextList(test) => test.test_filename(),
+            Self::HlSignedCiphertextList(test) => test.test_filename(),
+            Self::HlBoolCiphertextList(test) => test.test_filename(),
+            Self::HlClientKey(test) => test.test_filename(),
+            Self::HlServerKey(test) => test.test_filename(),
+            Self::HlPublicKey(test) => test.test_filename(),
+            Self::Unsupported(test) => test.test_filename.to_string(),
+        }
+    }
+}
+
+/// The version tags of a stored object, ie: the variants of its versioned enums (eg: `V0`)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+pub struct VersionTags {
+    /// The tag of the object itself
+    pub top_level: String,
+    /// The tags of the nested versioned types, by their path in the object
+    #[serde(default)]
+    pub nested: BTreeMap<String, String>,
+}
+
+/// Tag of the testcases that are quick to load
+pub const FAST_TAG: &str = "fast";
+/// Tag of the testcases that take long to load, especially in debug builds
+pub const SLOW_TAG: &str = "slow";
+/// Tag of the testcases whose data are large
+pub const LARGE_TAG: &str = "large";
+/// Tag of the testcases that hold a key
+pub const KEY_TAG: &str = "key";
+/// Tag of the testcases that hold one or several ciphertexts
+pub const CIPHERTEXT_TAG: &str = "ciphertext";
+/// Tag of the testcases that cannot be parsed by this version of the crate
+pub const UNSUPPORTED_TAG: &str = "unsupported";
+
+/// The rust type of a stored object, and how it was built
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+pub struct StoredType {
+    /// The full path of the type, as given by [`std::any::type_name`]
+    pub type_name: String,
+    /// The width of the encrypted integers, for integer ciphertexts
+    #[serde(default)]
+    pub num_bits: Option<u32>,
+    /// The function used to build the object (eg: `CompressedFheBool::encrypt`)
+    pub construction: String,
+}
+
+impl StoredType {
+    pub fn new<T: ?Sized>(construction: &str) -> Self {
+        Self {
+            type_name: std::any::type_name::<T>().to_string(),
+            num_bits: None,
+            construction: construction.to_string(),
+        }
+    }
+
+    pub fn with_num_bits(self, num_bits: u32) -> Self {
+        Self {
+            num_bits: Some(num_bits),
+            ..self
+        }
+    }
+
+    /// The name of the type, without its path and its generic parameters (eg: `FheUint`)
+    pub fn short_name(&self) -> &str {
+        short_type_name(&self.type_name)
+    }
+}
+
+/// Removes the path and the generic parameters from a type name
+fn short_type_name(type_name: &str) -> &str {
+    let base = type_name.split('<').next().unwrap_or(type_name);
+    base.rsplit("::").next().unwrap_or(base)
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
 pub struct Testcase {
     pub tfhe_version_min: String,
     pub tfhe_module: String,
     pub metadata: TestMetadata,
+    /// The version tags of the stored data, extracted when they were generated. The tags of the
+    /// data generated before they were recorded are added by the `backfill` command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_tags: Option<VersionTags>,
+    /// The type of the stored object, recorded when it was generated. The types of the data
+    /// generated before they were recorded are added by running the generator of their version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stored_type: Option<StoredType>,
+    /// Free-form tags used to select the testcases to run (eg: the module, the kind of object,
+    /// [`FAST_TAG`] or [`SLOW_TAG`])
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+}
+
+/// Identifies a testcase among the testcases of all the versions and modules. This is also the
+/// location of its files, relative to the data directory and without extension.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TestcaseId {
+    pub version: String,
+    pub module: String,
+    pub test_filename: String,
+}
+
+impl fmt::Display for TestcaseId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.version, self.module, self.test_filename)
+    }
+}
+
+impl Testcase {
+    pub fn id(&self) -> TestcaseId {
+        TestcaseId {
+            version: self.tfhe_version_min.clone(),
+            module: self.tfhe_module.clone(),
+            test_filename: self.metadata.test_filename(),
+        }
+    }
+
+    /// The ids of the testcases this one depends on (eg: the client key used to decrypt a
+    /// ciphertext). They are stored in the same version and module as this testcase.
+    pub fn auxiliary_ids(&self) -> Vec<TestcaseId> {
+        self.metadata
+            .auxiliary_filenames()
+            .into_iter()
+            .map(|test_filename| TestcaseId {
+                version: self.tfhe_version_min.clone(),
+                module: self.tfhe_module.clone(),
+                test_filename,
+            })
+            .collect()
+    }
+
+    /// Finds the testcases this one depends on in `testcases`. Every auxiliary file is a key, so
+    /// the referenced testcases should be client keys. Unsupported testcases are accepted, since
+    /// their kind is not known.
+    pub fn resolve_auxiliaries<'a>(
+        &self,
+        testcases: &'a [Testcase],
+    ) -> Result<Vec<&'a Testcase>, String> {
+        self.auxiliary_ids()
+            .into_iter()
+            .map(|aux_id| {
+                let auxiliary = testcases
+                    .iter()
+                    .find(|testcase| testcase.id() == aux_id)
+                    .ok_or_else(|| {
+                        format!(
+                            "Testcase {} references {}, which is not a testcase",
+                            self.id(),
+                            aux_id
+                        )
+                    })?;
+
+                match auxiliary.metadata {
+                    TestMetadata::ShortintClientKey(_)
+                    | TestMetadata::HlClientKey(_)
+                    | TestMetadata::Unsupported(_) => Ok(auxiliary),
+                    _ => Err(format!(
+                        "Testcase {} references {}, which is a {} instead of a client key",
+                        self.id(),
+                        aux_id,
+                        auxiliary.metadata
+                    )),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Checks that the auxiliary files referenced by each testcase are declared as testcases too, so
+/// that their parameters are known. Returns an error listing all the invalid references otherwise.
+pub fn check_references(testcases: &[Testcase]) -> Result<(), String> {
+    let errors: Vec<String> = testcases
+        .iter()
+        .filter_map(|testcase| testcase.resolve_auxiliaries(testcases).err())
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Checks that the testcases can be stored together: each one should have a unique id, and their
+/// files should not be mistaken for the files of another testcase (eg: on a case-insensitive file
+/// system). Returns an error listing all the conflicting testcases otherwise.
+pub fn check_unique_testcases(testcases: &[Testcase]) -> Result<(), String> {
+    let mut errors = Vec::new();
+    let mut ids: BTreeMap<TestcaseId, usize> = BTreeMap::new();
+    // Filenames of the testcases of each version and module, grouped by their lowercase form
+    let mut filenames: BTreeMap<(String, String, String), BTreeSet<String>> = BTreeMap::new();
+
+    for testcase in testcases {
+        let id = testcase.id();
+        let filename = &id.test_filename;
+
+        if filename.is_empty()
+            || filename.contains(['/', '\\'])
+            || DATA_EXTENSIONS
+                .iter()
+                .any(|extension| filename.ends_with(&format!(".{}", extension)))
+        {
+            errors.push(format!(
+                "Invalid test filename for testcase {}, expected a name without path or extension",
+                id
+            ));
+        }
+
+        filenames
+            .entry((
+                id.version.clone(),
+                id.module.clone(),
+                filename.to_lowercase(),
+            ))
+            .or_default()
+            .insert(filename.clone());
+        *ids.entry(id).or_default() += 1;
+    }
+
+    for (id, count) in ids {
+        if count > 1 {
+            errors.push(format!(
+                "Duplicate testcase {}, defined {} times",
+                id, count
+            ));
+        }
+    }
+
+    for ((version, module, _), colliding) in filenames {
+        if colliding.len() > 1 {
+            let colliding: Vec<String> = colliding.into_iter().collect();
+            errors.push(format!(
+                "Colliding testcases in {}/{}: {} only differ by their case",
+                version,
+                module,
+                colliding.join(", ")
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
 }
 
 #[cfg(feature = "load")]
 impl Testcase {
+    /// Checks that `T`, the type used to load the data of this testcase, has the same name as
+    /// the type that was stored. The check passes if the stored type was not recorded.
+    pub fn check_stored_type<T: ?Sized>(&self) -> Result<(), String> {
+        let Some(stored_type) = &self.stored_type else {
+            return Ok(());
+        };
+
+        let loaded_type = std::any::type_name::<T>();
+        if short_type_name(loaded_type) == stored_type.short_name() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Testcase {} stores a {} but is loaded as a {}",
+                self.id(),
+                stored_type.type_name,
+                loaded_type
+            ))
+        }
+    }
+
     pub fn is_valid_for_version(&self, version: &str) -> bool {
         let tfhe_version = Version::parse(version).unwrap();
 
@@ -366,10 +1115,139 @@ impl Testcase {
         min_version.matches(&tfhe_version)
     }
 
+    /// The result of this testcase when it is not supported by the tested version of tfhe-rs
     pub fn skip(&self) -> load::TestSkipped {
+        self.skip_with_reason(format!("requires tfhe-rs >= {}", self.tfhe_version_min))
+    }
+
+    /// The result of this testcase when it is not run, for the given `reason`
+    pub fn skip_with_reason(&self, reason: String) -> load::TestSkipped {
         load::TestSkipped {
             module: self.tfhe_module.to_string(),
             test_name: self.metadata.to_string(),
+            reason,
+        }
+    }
+
+    /// The result of this testcase in `format` when it cannot be run because the `prerequisite`
+    /// testcase failed
+    pub fn blocked(&self, format: load::DataFormat, prerequisite: TestcaseId) -> load::TestBlocked {
+        load::TestBlocked {
+            module: self.tfhe_module.to_string(),
+            test_name: self.metadata.to_string(),
+            test_filename: self.metadata.test_filename(),
+            format,
+            prerequisite,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{hl_ciphertext, parse_testcases, temp_dir};
+
+    /// Creates a directory that holds a `data` sub-directory with the metadata files, and a
+    /// nested `crate/src` directory
+    fn write_repository(name: &str) -> PathBuf {
+        let root = temp_dir(&format!("locate_{}", name));
+        fs::create_dir_all(root.join("crate").join("src")).unwrap();
+        fs::create_dir_all(data_dir(&root)).unwrap();
+        for (_, filename) in METADATA_FILES {
+            fs::write(data_dir(&root).join(filename), "").unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn data_dir_is_found_in_the_parents() {
+        let root = write_repository("parents");
+        let nested = root.join("crate").join("src");
+
+        let from_nested = find_data_dir(None, &[nested]);
+        let from_data_dir = find_data_dir(None, &[data_dir(&root)]);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(from_nested, Ok(data_dir(&root)));
+        assert_eq!(from_data_dir, Ok(data_dir(&root)));
+    }
+
+    #[test]
+    fn env_var_takes_precedence() {
+        let root = write_repository("env");
+        let start_dirs = [root.join("crate")];
+
+        let valid = find_data_dir(Some(data_dir(&root)), &start_dirs);
+        let invalid = find_data_dir(Some(root.clone()), &start_dirs);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(valid, Ok(data_dir(&root)));
+        let error = invalid.unwrap_err();
+        assert!(error.contains(DATA_DIR_ENV_VAR), "{}", error);
+    }
+
+    #[test]
+    fn searched_dirs_are_listed() {
+        let root = temp_dir("locate_missing");
+
+        let result = find_data_dir(None, std::slice::from_ref(&root));
+        fs::remove_dir_all(&root).unwrap();
+
+        let error = result.unwrap_err();
+        assert!(error.contains(&root.display().to_string()), "{}", error);
+        assert!(
+            error.contains(&data_dir(&root).display().to_string()),
+            "{}",
+            error
+        );
+    }
+
+    /// Parses the high level api ciphertexts stored in the test files `filenames`, of the given
+    /// versions
+    fn ciphertexts(filenames: &[(&str, &str)]) -> Vec<Testcase> {
+        let testcases: Vec<String> = filenames
+            .iter()
+            .map(|(version, filename)| hl_ciphertext(version, filename, "client_key"))
+            .collect();
+        parse_testcases(&testcases)
+    }
+
+    #[test]
+    fn distinct_testcases_are_unique() {
+        let testcases = ciphertexts(&[("0.6", "ct1"), ("0.6", "ct2"), ("0.7", "ct1")]);
+        assert_eq!(check_unique_testcases(&testcases), Ok(()));
+    }
+
+    #[test]
+    fn duplicate_testcases_are_rejected() {
+        let testcases = ciphertexts(&[("0.6", "ct"), ("0.6", "ct"), ("0.6", "ct")]);
+        let error = check_unique_testcases(&testcases).unwrap_err();
+        assert!(
+            error.contains("Duplicate testcase 0.6/high_level_api/ct, defined 3 times"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn filenames_differing_by_case_collide() {
+        let testcases = ciphertexts(&[("0.6", "ct"), ("0.6", "CT"), ("0.7", "Ct")]);
+        let error = check_unique_testcases(&testcases).unwrap_err();
+        assert!(
+            error.contains("Colliding testcases in 0.6/high_level_api: CT, ct"),
+            "{}",
+            error
+        );
+        // The testcases of different versions are stored in different directories
+        assert!(!error.contains("0.7"), "{}", error);
+    }
+
+    #[test]
+    fn filenames_with_a_path_or_an_extension_are_rejected() {
+        for filename in ["", "dir/ct", "dir\\ct", "ct.cbor", "ct.bcode"] {
+            let testcases = ciphertexts(&[("0.6", filename)]);
+            let error = check_unique_testcases(&testcases).unwrap_err();
+            assert!(error.contains("Invalid test filename"), "{}", error);
         }
     }
 }
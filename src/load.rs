@@ -1,35 +1,52 @@
 use std::{
+    any::{Any, TypeId},
+    collections::{BTreeMap, HashMap},
     fmt::Display,
     fs::{self, File},
-    path::Path,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
 };
 
 use bincode::{DefaultOptions, Options};
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Serialize};
 
-use crate::{TestType, Testcase};
+use crate::{
+    check_references, check_unique_testcases,
+    filter::FilteredTest,
+    inspect::inspect_cbor,
+    metadata::parse_metadata,
+    parallel_map,
+    schema::{schema_path, Schema},
+    TestMetadata, TestType, Testcase, TestcaseId, VersionTags,
+};
 
-/// Loads auxiliary data that might be needed for a test (eg: a key to test a ciphertext)
+/// Loads auxiliary data that might be needed for a test (eg: a key to test a ciphertext).
+///
+/// The format is inferred from the extension of the file, and an error is returned if the
+/// extension is missing or is not the one of a [`DataFormat`]. Prefer
+/// [`DataFormat::load_versioned_auxiliary`] to load the auxiliary with the same format as the
+/// test that uses it.
 pub fn load_versioned_auxiliary<Data: DeserializeOwned, P: AsRef<Path>>(
     path: P,
 ) -> Result<Data, String> {
-    let file = File::open(path.as_ref()).map_err(|e| {
-        format!(
-            "Failed to read auxiliary file {}: {}",
-            path.as_ref().display(),
-            e
-        )
-    })?;
-    ciborium::de::from_reader(file).map_err(|e| {
-        format!(
-            "Failed to parse auxiliary file {}: {}",
-            path.as_ref().display(),
-            e
-        )
-    })
+    let path = path.as_ref();
+    let format = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(DataFormat::from_extension)
+        .ok_or_else(|| {
+            format!(
+                "Failed to load auxiliary file {}: unknown data format, expected a .cbor or \
+                .bcode file",
+                path.display()
+            )
+        })?;
+
+    format.load_from_path(path)
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum DataFormat {
     Cbor,
     Bincode,
@@ -42,6 +59,9 @@ impl Display for DataFormat {
 }
 
 impl DataFormat {
+    /// All the formats in which the data are stored
+    pub const ALL: [DataFormat; 2] = [DataFormat::Cbor, DataFormat::Bincode];
+
     pub fn extension(&self) -> &'static str {
         match self {
             DataFormat::Cbor => "cbor",
@@ -49,32 +69,488 @@ impl DataFormat {
         }
     }
 
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "cbor" => Some(DataFormat::Cbor),
+            "bcode" => Some(DataFormat::Bincode),
+            _ => None,
+        }
+    }
+
+    /// Returns the path of a file stored in `dir` in this format, `filename` being given without
+    /// extension
+    pub fn path_in<P: AsRef<Path>>(self, dir: P, filename: &str) -> PathBuf {
+        dir.as_ref()
+            .join(format!("{}.{}", filename, self.extension()))
+    }
+
+    /// Deserializes a versioned object read from `reader`, using this format
+    pub fn load_from_reader<Data: DeserializeOwned, R: Read>(
+        self,
+        reader: R,
+    ) -> Result<Data, String> {
+        match self {
+            Self::Cbor => ciborium::de::from_reader(reader)
+                .map_err(|e| format!("Failed to deserialize {} data: {}", self, e)),
+            Self::Bincode => {
+                let options = DefaultOptions::new().with_fixint_encoding();
+                options
+                    .deserialize_from(reader)
+                    .map_err(|e| format!("Failed to deserialize {} data: {}", self, e))
+            }
+        }
+    }
+
+    /// Deserializes a versioned object from memory, using this format
+    pub fn load_from_bytes<Data: DeserializeOwned>(self, bytes: &[u8]) -> Result<Data, String> {
+        self.load_from_reader(bytes)
+    }
+
+    fn load_from_path<Data: DeserializeOwned, P: AsRef<Path>>(
+        self,
+        path: P,
+    ) -> Result<Data, String> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|e| format!("Failed to read file {}: {}", path.display(), e))?;
+
+        self.load_from_reader(BufReader::new(file))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Serializes a versioned object in memory, using this format
+    fn serialize<Data: Serialize>(self, versioned: &Data) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Cbor => {
+                let mut serialized = Vec::new();
+                ciborium::ser::into_writer(versioned, &mut serialized)
+                    .map_err(|e| format!("Failed to serialize in {}: {}", self, e))?;
+                Ok(serialized)
+            }
+            Self::Bincode => {
+                let options = DefaultOptions::new().with_fixint_encoding();
+                options
+                    .serialize(versioned)
+                    .map_err(|e| format!("Failed to serialize in {}: {}", self, e))
+            }
+        }
+    }
+
     /// Loads the file that should be tested
     pub fn load_versioned_test<Data: DeserializeOwned, P: AsRef<Path>, T: TestType>(
         self,
         dir: P,
         test: &T,
     ) -> Result<Data, TestFailure> {
-        let filename = format!("{}.{}", test.test_filename(), self.extension());
-        let file = File::open(dir.as_ref().join(filename))
-            .map_err(|e| test.failure(format!("Failed to read testcase: {}", e), self))?;
+        self.load_from_path(self.path_in(dir, &test.test_filename()))
+            .map_err(|e| test.failure(e, self))
+    }
 
-        match self {
-            Self::Cbor => ciborium::de::from_reader(file).map_err(|e| test.failure(e, self)),
+    /// Loads the data that should be tested from `reader` (eg: a file in an archive)
+    pub fn load_versioned_test_from_reader<Data: DeserializeOwned, R: Read, T: TestType>(
+        self,
+        reader: R,
+        test: &T,
+    ) -> Result<Data, TestFailure> {
+        self.load_from_reader(reader)
+            .map_err(|e| test.failure(e, self))
+    }
+
+    /// Loads the data that should be tested from memory (eg: data embedded in the test binary)
+    pub fn load_versioned_test_from_bytes<Data: DeserializeOwned, T: TestType>(
+        self,
+        bytes: &[u8],
+        test: &T,
+    ) -> Result<Data, TestFailure> {
+        self.load_versioned_test_from_reader(bytes, test)
+    }
+
+    /// Checks that the version tags of the data of `testcase` stored in `dir` match the ones
+    /// recorded in the metadata. The check passes if no tag was recorded for the testcase, the tags
+    /// of the data generated before they were recorded are added by the `backfill` command.
+    ///
+    /// Bincode is not self-describing, so only the top-level tag can be checked for this format:
+    /// it is read from the variant index of the versioned enum, encoded in the first 4 bytes.
+    pub fn check_version_tags<P: AsRef<Path>>(
+        self,
+        dir: P,
+        testcase: &Testcase,
+    ) -> Result<(), String> {
+        let path = self.path_in(dir, &testcase.metadata.test_filename());
+        let file = File::open(&path)
+            .map_err(|e| format!("Failed to read file {}: {}", path.display(), e))?;
+
+        self.check_version_tags_from_reader(BufReader::new(file), testcase)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Same as [`Self::check_version_tags`], with the data of `testcase` read from `reader`
+    pub fn check_version_tags_from_reader<R: Read>(
+        self,
+        mut reader: R,
+        testcase: &Testcase,
+    ) -> Result<(), String> {
+        let Some(expected) = &testcase.version_tags else {
+            return Ok(());
+        };
+
+        let found = match self {
+            Self::Cbor => inspect_cbor(reader)
+                .map(|node| Schema::from_node(&node).version_tags())?
+                .ok_or_else(|| "The data do not hold a versioned object".to_string())?,
             Self::Bincode => {
-                let options = DefaultOptions::new().with_fixint_encoding();
-                options
-                    .deserialize_from(file)
-                    .map_err(|e| test.failure(e, self))
+                let mut variant_index = [0u8; 4];
+                reader
+                    .read_exact(&mut variant_index)
+                    .map_err(|e| format!("Failed to read data: {}", e))?;
+
+                VersionTags {
+                    top_level: format!("V{}", u32::from_le_bytes(variant_index)),
+                    nested: BTreeMap::new(),
+                }
+            }
+        };
+
+        let mut errors = Vec::new();
+        if found.top_level != expected.top_level {
+            errors.push(format!(
+                "expected {}, found {}",
+                expected.top_level, found.top_level
+            ));
+        }
+        if self == Self::Cbor {
+            for (tag_path, tag) in expected.nested.iter() {
+                match found.nested.get(tag_path) {
+                    Some(found_tag) if found_tag == tag => {}
+                    found_tag => errors.push(format!(
+                        "{}: expected {}, found {}",
+                        tag_path,
+                        tag,
+                        found_tag.map_or("no version", String::as_str)
+                    )),
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Unexpected version tags: {}", errors.join("; ")))
+        }
+    }
+
+    /// Same as [`Self::check_version_tags`], with the data of `testcase` stored in memory
+    pub fn check_version_tags_from_bytes(
+        self,
+        bytes: &[u8],
+        testcase: &Testcase,
+    ) -> Result<(), String> {
+        self.check_version_tags_from_reader(bytes, testcase)
+    }
+
+    /// Loads auxiliary data that might be needed for a test (eg: a key to test a ciphertext),
+    /// using this format. `filename` is given without extension, as it is stored in the
+    /// metadata (eg: `key_filename`).
+    pub fn load_versioned_auxiliary<Data: DeserializeOwned, P: AsRef<Path>>(
+        self,
+        dir: P,
+        filename: &str,
+    ) -> Result<Data, String> {
+        self.load_from_path(self.path_in(dir, filename))
+            .map_err(|e| format!("Failed to load auxiliary file: {}", e))
+    }
+
+    /// Loads auxiliary data read from `reader`, using this format
+    pub fn load_versioned_auxiliary_from_reader<Data: DeserializeOwned, R: Read>(
+        self,
+        reader: R,
+    ) -> Result<Data, String> {
+        self.load_from_reader(reader)
+            .map_err(|e| format!("Failed to load auxiliary data: {}", e))
+    }
+
+    /// Loads auxiliary data stored in memory, using this format
+    pub fn load_versioned_auxiliary_from_bytes<Data: DeserializeOwned>(
+        self,
+        bytes: &[u8],
+    ) -> Result<Data, String> {
+        self.load_versioned_auxiliary_from_reader(bytes)
+    }
+}
+
+/// Checks that the layout of `versioned`, an object of the type of the testcase serialized with
+/// the current tfhe-rs, matches the schema snapshot stored next to the test data. This catches
+/// layout changes that were not versioned, even if the old data can still be deserialized.
+///
+/// The check passes if no snapshot was recorded for this testcase. The snapshots of the data
+/// generated before they were introduced are extracted from their cbor data by the `backfill`
+/// command.
+pub fn check_schema_snapshot<Data: Serialize, P: AsRef<Path>, T: TestType>(
+    dir: P,
+    test: &T,
+    versioned: &Data,
+) -> Result<(), String> {
+    let path = schema_path(dir, &test.test_filename());
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let snapshot = Schema::load(path)?;
+    let current = Schema::of_value(versioned)?;
+    snapshot.check_compatible(&current)
+}
+
+/// Same as [`check_schema_snapshot`], with the content of the `.schema.ron` snapshot given as a
+/// string. The check passes if there is no snapshot.
+pub fn check_schema_snapshot_from_str<Data: Serialize>(
+    snapshot: Option<&str>,
+    versioned: &Data,
+) -> Result<(), String> {
+    let Some(snapshot) = snapshot else {
+        return Ok(());
+    };
+
+    let snapshot = Schema::parse(snapshot)?;
+    let current = Schema::of_value(versioned)?;
+    snapshot.check_compatible(&current)
+}
+
+/// Checks that an object loaded from the test data can be used with the current tfhe-rs: it is
+/// versioned again with `versionize`, serialized and deserialized in memory in all the
+/// [`DataFormat`]s, and upgraded again with `unversionize`. The object obtained for each format
+/// is decrypted with `decrypt`, and should give the `expected` clear value(s) of the metadata.
+///
+/// The first format for which the round trip fails is returned in the error.
+pub fn check_round_trip<'a, T, Obj, Versioned, VersionedOwned, Clear, E, V, U, D>(
+    test: &T,
+    loaded: &'a Obj,
+    versionize: V,
+    unversionize: U,
+    decrypt: D,
+    expected: &Clear,
+) -> Result<(), TestFailure>
+where
+    T: TestType,
+    Versioned: Serialize,
+    VersionedOwned: DeserializeOwned,
+    Clear: PartialEq + std::fmt::Debug,
+    E: Display,
+    V: FnOnce(&'a Obj) -> Versioned,
+    U: Fn(VersionedOwned) -> Result<Obj, E>,
+    D: Fn(&Obj) -> Clear,
+{
+    let versioned = versionize(loaded);
+
+    for format in DataFormat::ALL {
+        let serialized = format
+            .serialize(&versioned)
+            .map_err(|e| test.failure(e, format))?;
+        let deserialized: VersionedOwned = format
+            .load_from_bytes(&serialized)
+            .map_err(|e| test.failure(e, format))?;
+        let obj = unversionize(deserialized).map_err(|e| {
+            test.failure(
+                format!("Failed to unversionize after round trip: {}", e),
+                format,
+            )
+        })?;
+
+        let clear = decrypt(&obj);
+        if &clear != expected {
+            return Err(test.failure(
+                format!(
+                    "Invalid value after round trip: expected {:?}, got {:?}",
+                    expected, clear
+                ),
+                format,
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+type CacheKey = (PathBuf, DataFormat, TypeId);
+
+/// The data of an entry, that is set once by the first thread that deserializes it. The other
+/// threads that need the same entry in the meantime wait for it instead of deserializing it again.
+type CacheSlot = Arc<OnceLock<Result<Arc<dyn Any + Send + Sync>, String>>>;
+
+struct CacheEntry {
+    slot: CacheSlot,
+    /// The size of the file of the data, 0 while it is being deserialized
+    size: usize,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheEntries {
+    entries: HashMap<CacheKey, CacheEntry>,
+    total_size: usize,
+    clock: u64,
+}
+
+impl CacheEntries {
+    /// Evicts the least recently used entries until the total size fits in `max_size`. The
+    /// entries that are being deserialized are kept.
+    fn evict(&mut self, max_size: usize) {
+        while self.total_size > max_size {
+            let Some(lru_key) = self
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.slot.get().is_some())
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+
+            if let Some(entry) = self.entries.remove(&lru_key) {
+                self.total_size -= entry.size;
+            }
+        }
+    }
+}
+
+/// A cache for deserialized auxiliary data (eg: keys), that can be shared between testcases.
+///
+/// Entries are identified by their path, format and type. The size of an entry is the size of its
+/// serialized file, not the memory used by the deserialized data, which may be larger. The least
+/// recently used entries are evicted when the total exceeds the size given at construction.
+///
+/// An entry is only deserialized once: the threads that need it while it is being deserialized
+/// wait for the result.
+pub struct AuxiliaryCache {
+    max_size: usize,
+    cache: Mutex<CacheEntries>,
+}
+
+impl AuxiliaryCache {
+    /// Creates a cache that will hold at most `max_size` bytes of auxiliary data, measured by the
+    /// size of their files
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            cache: Mutex::new(CacheEntries::default()),
+        }
+    }
+
+    /// Creates a cache that never evicts its entries
+    pub fn unbounded() -> Self {
+        Self::new(usize::MAX)
+    }
+
+    /// Loads auxiliary data in the given format, or returns it from the cache if it has already
+    /// been loaded. See [`DataFormat::load_versioned_auxiliary`].
+    pub fn load_versioned_auxiliary<Data, P>(
+        &self,
+        format: DataFormat,
+        dir: P,
+        filename: &str,
+    ) -> Result<Arc<Data>, String>
+    where
+        Data: DeserializeOwned + Send + Sync + 'static,
+        P: AsRef<Path>,
+    {
+        let path = format.path_in(&dir, filename);
+        let key = (path.clone(), format, TypeId::of::<Data>());
+        let (slot, inserted) = self.slot(&key);
+
+        // The lock is not held during deserialization so that other threads can still use the
+        // cache in the meantime.
+        let data = slot
+            .get_or_init(|| {
+                format
+                    .load_versioned_auxiliary::<Data, _>(dir, filename)
+                    .map(|data| Arc::new(data) as Arc<dyn Any + Send + Sync>)
+            })
+            .clone();
+
+        // The thread that inserted the entry records its size once it is known, or removes it if
+        // the data could not be loaded
+        if inserted {
+            let size = fs::metadata(&path)
+                .map(|metadata| metadata.len() as usize)
+                .unwrap_or(0);
+            self.set_size(&key, &slot, data.is_ok().then_some(size));
+        }
+
+        data.map(|data| data.downcast().unwrap())
+    }
+
+    /// Returns the slot of the entry of `key`, and whether it was inserted by this call
+    fn slot(&self, key: &CacheKey) -> (CacheSlot, bool) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.clock += 1;
+        let last_used = cache.clock;
+
+        if let Some(entry) = cache.entries.get_mut(key) {
+            entry.last_used = last_used;
+            return (entry.slot.clone(), false);
+        }
+
+        let slot = CacheSlot::default();
+        let entry = CacheEntry {
+            slot: slot.clone(),
+            size: 0,
+            last_used,
+        };
+        cache.entries.insert(key.clone(), entry);
+        (slot, true)
+    }
+
+    /// Records the size of the entry of `key`, if it still holds `slot`. The entry is removed if
+    /// `size` is `None` or does not fit in the cache.
+    fn set_size(&self, key: &CacheKey, slot: &CacheSlot, size: Option<usize>) {
+        let mut cache = self.cache.lock().unwrap();
+        if !cache
+            .entries
+            .get(key)
+            .is_some_and(|entry| Arc::ptr_eq(&entry.slot, slot))
+        {
+            return;
+        }
+
+        match size.filter(|size| *size <= self.max_size) {
+            Some(size) => {
+                cache.entries.get_mut(key).unwrap().size = size;
+                cache.total_size += size;
+                cache.evict(self.max_size);
+            }
+            None => {
+                cache.entries.remove(key);
             }
         }
     }
+
+    /// Total size of the files of the cached data, in bytes
+    pub fn size(&self) -> usize {
+        self.cache.lock().unwrap().total_size
+    }
+
+    /// Number of entries in the cache
+    pub fn len(&self) -> usize {
+        self.cache.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all the entries from the cache
+    pub fn clear(&self) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.entries.clear();
+        cache.total_size = 0;
+    }
 }
 
 pub enum TestResult {
     Success(TestSuccess),
     Failure(TestFailure),
     Skipped(TestSkipped),
+    /// The test was not run because a testcase it depends on failed
+    Blocked(TestBlocked),
 }
 
 impl From<Result<TestSuccess, TestFailure>> for TestResult {
@@ -90,6 +566,15 @@ impl TestResult {
     pub fn is_failure(&self) -> bool {
         match self {
             TestResult::Failure(_) => true,
+            TestResult::Success(_) | TestResult::Skipped(_) | TestResult::Blocked(_) => false,
+        }
+    }
+
+    /// Returns true if the test failed or was blocked by a failure: the testcases that depend
+    /// on it should not be run
+    fn blocks_dependents(&self) -> bool {
+        match self {
+            TestResult::Failure(_) | TestResult::Blocked(_) => true,
             TestResult::Success(_) | TestResult::Skipped(_) => false,
         }
     }
@@ -140,16 +625,537 @@ impl Display for TestSuccess {
 pub struct TestSkipped {
     pub(crate) module: String,
     pub(crate) test_name: String,
+    pub(crate) reason: String,
 }
 
 impl Display for TestSkipped {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Test: {}::{}: SKIPPED", self.module, self.test_name)
+        write!(
+            f,
+            "Test: {}::{}: SKIPPED: {}",
+            self.module, self.test_name, self.reason
+        )
+    }
+}
+
+pub struct TestBlocked {
+    pub(crate) module: String,
+    pub(crate) test_name: String,
+    pub(crate) test_filename: String,
+    pub(crate) format: DataFormat,
+    /// The first testcase this one depends on that failed
+    pub(crate) prerequisite: TestcaseId,
+}
+
+impl Display for TestBlocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Test: {}::{} in file {}.{}: BLOCKED by the failure of {}",
+            self.module,
+            self.test_name,
+            self.test_filename,
+            self.format.extension(),
+            self.prerequisite
+        )
     }
 }
 
+/// Loads the testcases from the metadata file at `path`, and checks that they are consistent
 pub fn load_tests_metadata<P: AsRef<Path>>(path: P) -> Result<Vec<Testcase>, String> {
     let serialized =
         fs::read_to_string(path).map_err(|e| format!("Failed to load test metadata: {}", e))?;
-    ron::from_str(&serialized).map_err(|e| format!("Invalid test metadata: {}", e))
+    load_tests_metadata_from_str(&serialized)
+}
+
+/// Same as [`load_tests_metadata`], with the content of the metadata file given as a string
+pub fn load_tests_metadata_from_str(serialized: &str) -> Result<Vec<Testcase>, String> {
+    let testcases =
+        parse_metadata(serialized).map_err(|e| format!("Invalid test metadata: {}", e))?;
+
+    check_unique_testcases(&testcases)
+        .and_then(|_| check_references(&testcases))
+        .map_err(|e| format!("Invalid test metadata: {}", e))?;
+    Ok(testcases)
+}
+
+/// Environment variable holding the filter applied by [`run_tests`], in the syntax of
+/// [`TestFilter::parse`]
+pub const FILTER_ENV_VAR: &str = "TFHE_BACKWARD_COMPAT_FILTER";
+
+pub use crate::filter::TestFilter;
+
+impl TestFilter {
+    /// Reads the filter from the [`FILTER_ENV_VAR`] environment variable. The filter is empty if
+    /// the variable is not set.
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var(FILTER_ENV_VAR) {
+            Ok(filter) => Self::parse(&filter)
+                .map_err(|e| format!("Invalid {} variable: {}", FILTER_ENV_VAR, e)),
+            Err(std::env::VarError::NotPresent) => Ok(Self::default()),
+            Err(e) => Err(format!("Invalid {} variable: {}", FILTER_ENV_VAR, e)),
+        }
+    }
+
+    /// Returns the reason why the test of `testcase` in `format` is not selected, or `None` if
+    /// it is selected
+    pub fn rejection_reason(&self, testcase: &Testcase, format: DataFormat) -> Option<String> {
+        // The unsupported testcases are selected on the kind written in the metadata
+        let kind = match &testcase.metadata {
+            TestMetadata::Unsupported(test) => test.kind.clone(),
+            metadata => metadata.to_string(),
+        };
+
+        self.rejection_reason_of(&FilteredTest {
+            version: &testcase.tfhe_version_min,
+            module: &testcase.tfhe_module,
+            kind: &kind,
+            tags: &testcase.tags,
+            extension: format.extension(),
+        })
+    }
+
+    pub fn selects(&self, testcase: &Testcase, format: DataFormat) -> bool {
+        self.rejection_reason(testcase, format).is_none()
+    }
+}
+
+/// Groups the testcases by dependency level: the testcases of a level only depend on the
+/// testcases of the previous levels (eg: the ciphertexts come after the client key used to decrypt
+/// them). The testcases are given by their index in `testcases`, and the dependencies on
+/// testcases that are not in `testcases` are ignored.
+pub fn dependency_levels(testcases: &[Testcase]) -> Vec<Vec<usize>> {
+    let indices: BTreeMap<TestcaseId, usize> = testcases
+        .iter()
+        .enumerate()
+        .map(|(idx, testcase)| (testcase.id(), idx))
+        .collect();
+    let dependencies: Vec<Vec<usize>> = testcases
+        .iter()
+        .map(|testcase| {
+            testcase
+                .auxiliary_ids()
+                .iter()
+                .filter_map(|aux_id| indices.get(aux_id).copied())
+                .collect()
+        })
+        .collect();
+
+    let mut levels = Vec::new();
+    let mut done = vec![false; testcases.len()];
+    let mut remaining: Vec<usize> = (0..testcases.len()).collect();
+
+    while !remaining.is_empty() {
+        let (ready, blocked): (Vec<usize>, Vec<usize>) = remaining
+            .iter()
+            .partition(|idx| dependencies[**idx].iter().all(|dep| done[*dep]));
+
+        // A cycle in the references cannot be ordered, so its testcases are run together
+        let level = if ready.is_empty() { blocked } else { ready };
+        for idx in level.iter() {
+            done[*idx] = true;
+        }
+        remaining.retain(|idx| !done[*idx]);
+        levels.push(level);
+    }
+
+    levels
+}
+
+/// Returns why `testcase` cannot be run by this version of the crate, if it is unsupported or if
+/// it depends on an unsupported testcase
+fn unsupported_reason(
+    testcase: &Testcase,
+    testcases: &[Testcase],
+    indices: &BTreeMap<TestcaseId, usize>,
+) -> Option<String> {
+    if let TestMetadata::Unsupported(test) = &testcase.metadata {
+        return Some(format!(
+            "unsupported kind of test {}: {}",
+            test.kind, test.reason
+        ));
+    }
+
+    testcase.auxiliary_ids().into_iter().find_map(|aux_id| {
+        let aux_idx = indices.get(&aux_id)?;
+        match &testcases[*aux_idx].metadata {
+            TestMetadata::Unsupported(_) => {
+                Some(format!("depends on the unsupported testcase {}", aux_id))
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Runs `run` on every pair of testcase and format selected by the filter given in the
+/// [`FILTER_ENV_VAR`] environment variable, using `workers` threads. If `workers` is 0, the number
+/// of threads is given by [`std::thread::available_parallelism`].
+///
+/// Returns an error if the filter is invalid, without running any test.
+pub fn run_tests<F>(
+    testcases: &[Testcase],
+    formats: &[DataFormat],
+    workers: usize,
+    run: F,
+) -> Result<Vec<TestResult>, String>
+where
+    F: Fn(&Testcase, DataFormat) -> TestResult + Sync,
+{
+    let filter = TestFilter::from_env()?;
+    Ok(run_filtered_tests(
+        testcases, formats, workers, &filter, run,
+    ))
+}
+
+/// Runs `run` on every pair of testcase and format selected by `filter`, using `workers`
+/// threads. The tests that are not selected are reported as [`TestResult::Skipped`], with the
+/// reason why they were not selected, as well as the unsupported testcases (see
+/// [`TestMetadata::Unsupported`]) and the testcases that depend on them.
+///
+/// The testcases are run after the testcases they depend on (see [`dependency_levels`]). If a
+/// testcase fails in a format, the testcases that depend on it are not run in this format, and
+/// are reported as [`TestResult::Blocked`] instead.
+///
+/// The results are returned in the same order as the metadata (see `TfhersVersion::testcases`):
+/// for each testcase, one result per format in the order of `formats`.
+pub fn run_filtered_tests<F>(
+    testcases: &[Testcase],
+    formats: &[DataFormat],
+    workers: usize,
+    filter: &TestFilter,
+    run: F,
+) -> Vec<TestResult>
+where
+    F: Fn(&Testcase, DataFormat) -> TestResult + Sync,
+{
+    let indices: BTreeMap<TestcaseId, usize> = testcases
+        .iter()
+        .enumerate()
+        .map(|(idx, testcase)| (testcase.id(), idx))
+        .collect();
+    // Results indexed by testcase, then by format
+    let mut results: Vec<Vec<Option<TestResult>>> = testcases
+        .iter()
+        .map(|_| formats.iter().map(|_| None).collect())
+        .collect();
+
+    for level in dependency_levels(testcases) {
+        let mut jobs = Vec::new();
+        for idx in level {
+            let testcase = &testcases[idx];
+            let unsupported_reason = unsupported_reason(testcase, testcases, &indices);
+
+            for (format_idx, format) in formats.iter().enumerate() {
+                if let Some(reason) = unsupported_reason
+                    .clone()
+                    .or_else(|| filter.rejection_reason(testcase, *format))
+                {
+                    results[idx][format_idx] =
+                        Some(TestResult::Skipped(testcase.skip_with_reason(reason)));
+                    continue;
+                }
+
+                let failed_prerequisite = testcase.auxiliary_ids().into_iter().find(|aux_id| {
+                    indices.get(aux_id).is_some_and(|aux_idx| {
+                        results[*aux_idx][format_idx]
+                            .as_ref()
+                            .is_some_and(TestResult::blocks_dependents)
+                    })
+                });
+
+                match failed_prerequisite {
+                    Some(prerequisite) => {
+                        results[idx][format_idx] =
+                            Some(TestResult::Blocked(testcase.blocked(*format, prerequisite)))
+                    }
+                    None => jobs.push((idx, format_idx)),
+                }
+            }
+        }
+
+        let level_results = parallel_map(&jobs, workers, |(idx, format_idx)| {
+            run(&testcases[*idx], formats[*format_idx])
+        });
+        for ((idx, format_idx), result) in jobs.into_iter().zip(level_results) {
+            results[idx][format_idx] = Some(result);
+        }
+    }
+
+    results
+        .into_iter()
+        .flatten()
+        .map(|result| result.expect("Every test should have a result"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::test_fixtures::{hl_ciphertext, parse_testcases, temp_dir};
+
+    /// Parses high level api ciphertexts of tfhe-rs 0.6 given by their test filename and the
+    /// filename of the testcase they reference
+    fn ciphertexts(references: &[(&str, &str)]) -> Vec<Testcase> {
+        let testcases: Vec<String> = references
+            .iter()
+            .map(|(filename, key_filename)| hl_ciphertext("0.6", filename, key_filename))
+            .collect();
+        parse_testcases(&testcases)
+    }
+
+    #[test]
+    fn testcases_come_after_their_dependencies() {
+        let testcases = ciphertexts(&[
+            ("c", "b"),
+            ("b", "a"),
+            ("a", "external"),
+            ("d", "a"),
+            ("x", "y"),
+            ("y", "x"),
+        ]);
+
+        // The testcases of a cycle cannot be ordered, so they come last, together
+        assert_eq!(
+            dependency_levels(&testcases),
+            vec![vec![2], vec![1, 3], vec![0], vec![4, 5]]
+        );
+    }
+
+    #[test]
+    fn failures_block_the_dependents_in_the_same_format() {
+        let testcases = ciphertexts(&[("c", "b"), ("b", "a"), ("a", "key"), ("d", "key")]);
+        let formats = [DataFormat::Cbor, DataFormat::Bincode];
+
+        let results = run_filtered_tests(
+            &testcases,
+            &formats,
+            2,
+            &TestFilter::default(),
+            |testcase, format| {
+                let test_filename = testcase.metadata.test_filename();
+                if test_filename == "a" && format == DataFormat::Cbor {
+                    TestResult::Failure(TestFailure {
+                        module: testcase.tfhe_module.clone(),
+                        target_type: testcase.metadata.to_string(),
+                        test_filename,
+                        source_error: "invalid data".to_string(),
+                        format,
+                    })
+                } else {
+                    TestResult::Success(TestSuccess {
+                        module: testcase.tfhe_module.clone(),
+                        target_type: testcase.metadata.to_string(),
+                        test_filename,
+                        format,
+                    })
+                }
+            },
+        );
+
+        let outcomes: Vec<String> = results
+            .iter()
+            .map(|result| match result {
+                TestResult::Success(success) => format!("{} ok", success.test_filename),
+                TestResult::Failure(failure) => format!("{} failed", failure.test_filename),
+                TestResult::Skipped(skipped) => format!("{} skipped", skipped.test_name),
+                TestResult::Blocked(blocked) => format!(
+                    "{} blocked by {}",
+                    blocked.test_filename, blocked.prerequisite.test_filename
+                ),
+            })
+            .collect();
+        // The results are in the order of the testcases, with one result per format
+        assert_eq!(
+            outcomes,
+            vec![
+                "c blocked by b",
+                "c ok",
+                "b blocked by a",
+                "b ok",
+                "a failed",
+                "a ok",
+                "d ok",
+                "d ok",
+            ]
+        );
+    }
+
+    /// Writes the cbor files `{name}.cbor` holding `values`, all of the same size, and returns the
+    /// size of a file
+    fn write_auxiliaries(dir: &Path, values: &[(&str, u64)]) -> usize {
+        for (name, value) in values {
+            let file = File::create(dir.join(format!("{}.cbor", name))).unwrap();
+            ciborium::ser::into_writer(&vec![*value; 10], file).unwrap();
+        }
+        fs::metadata(dir.join(format!("{}.cbor", values[0].0)))
+            .unwrap()
+            .len() as usize
+    }
+
+    #[test]
+    fn least_recently_used_auxiliaries_are_evicted() {
+        let dir = temp_dir("load_cache");
+        let size = write_auxiliaries(&dir, &[("a", 1), ("b", 2), ("c", 3)]);
+        let cache = AuxiliaryCache::new(2 * size);
+        let load = |name| {
+            cache
+                .load_versioned_auxiliary::<Vec<u64>, _>(DataFormat::Cbor, &dir, name)
+                .unwrap()
+        };
+
+        let a = load("a");
+        assert!(Arc::ptr_eq(&a, &load("a")));
+        let b = load("b");
+        // "a" is used after "b", so "b" is evicted to make room for "c"
+        load("a");
+        load("c");
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.size(), 2 * size);
+
+        assert!(Arc::ptr_eq(&a, &load("a")));
+        let reloaded_b = load("b");
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!Arc::ptr_eq(&b, &reloaded_b));
+        assert_eq!(*reloaded_b, vec![2; 10]);
+    }
+
+    #[test]
+    fn auxiliaries_larger_than_the_cache_are_not_kept() {
+        let dir = temp_dir("load_cache_large");
+        let size = write_auxiliaries(&dir, &[("a", 1)]);
+        let cache = AuxiliaryCache::new(size - 1);
+
+        let a = cache.load_versioned_auxiliary::<Vec<u64>, _>(DataFormat::Cbor, &dir, "a");
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(a.as_deref(), Ok(&vec![1; 10]));
+        assert!(cache.is_empty());
+        assert_eq!(cache.size(), 0);
+    }
+
+    #[test]
+    fn cached_auxiliaries_are_typed() {
+        let dir = temp_dir("load_cache_typed");
+        write_auxiliaries(&dir, &[("a", 1)]);
+        let cache = AuxiliaryCache::unbounded();
+
+        let as_u64 = cache.load_versioned_auxiliary::<Vec<u64>, _>(DataFormat::Cbor, &dir, "a");
+        let as_u32 = cache.load_versioned_auxiliary::<Vec<u32>, _>(DataFormat::Cbor, &dir, "a");
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(as_u64.as_deref(), Ok(&vec![1; 10]));
+        assert_eq!(as_u32.as_deref(), Ok(&vec![1; 10]));
+        assert_eq!(cache.len(), 2);
+    }
+
+    /// Counts its deserializations
+    struct CountedLoad;
+
+    static COUNTED_LOADS: AtomicUsize = AtomicUsize::new(0);
+
+    impl<'de> serde::Deserialize<'de> for CountedLoad {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Vec::<u64>::deserialize(deserializer)?;
+            COUNTED_LOADS.fetch_add(1, Ordering::SeqCst);
+            Ok(Self)
+        }
+    }
+
+    #[test]
+    fn concurrent_loads_deserialize_once() {
+        let dir = temp_dir("load_cache_concurrent");
+        write_auxiliaries(&dir, &[("a", 1)]);
+        let cache = AuxiliaryCache::unbounded();
+
+        let loaded: Vec<Arc<CountedLoad>> = std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..8)
+                .map(|_| {
+                    scope.spawn(|| {
+                        cache
+                            .load_versioned_auxiliary::<CountedLoad, _>(DataFormat::Cbor, &dir, "a")
+                            .unwrap()
+                    })
+                })
+                .collect();
+            threads
+                .into_iter()
+                .map(|thread| thread.join().unwrap())
+                .collect()
+        });
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(COUNTED_LOADS.load(Ordering::SeqCst), 1);
+        assert!(loaded.iter().all(|data| Arc::ptr_eq(data, &loaded[0])));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn failed_loads_are_not_cached() {
+        let dir = temp_dir("load_cache_failed");
+        let cache = AuxiliaryCache::unbounded();
+
+        let missing = cache.load_versioned_auxiliary::<Vec<u64>, _>(DataFormat::Cbor, &dir, "a");
+        write_auxiliaries(&dir, &[("a", 1)]);
+        let written = cache.load_versioned_auxiliary::<Vec<u64>, _>(DataFormat::Cbor, &dir, "a");
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(missing.is_err());
+        assert_eq!(written.as_deref(), Ok(&vec![1; 10]));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn missing_version_tags_are_skipped() {
+        let mut testcase = ciphertexts(&[("ct", "client_key")]).remove(0);
+        let mut serialized = Vec::new();
+        ciborium::ser::into_writer(&42u64, &mut serialized).unwrap();
+
+        let unchecked = DataFormat::Cbor.check_version_tags_from_bytes(&serialized, &testcase);
+        testcase.version_tags = Some(VersionTags {
+            top_level: "V0".to_string(),
+            nested: BTreeMap::new(),
+        });
+        let checked = DataFormat::Cbor.check_version_tags_from_bytes(&serialized, &testcase);
+
+        assert_eq!(unchecked, Ok(()));
+        assert!(checked.is_err());
+    }
+
+    #[test]
+    fn missing_schema_snapshots_are_skipped() {
+        let dir = temp_dir("load_no_snapshot");
+        let TestMetadata::HlCiphertext(test) = &ciphertexts(&[("ct", "client_key")])[0].metadata
+        else {
+            unreachable!()
+        };
+
+        let checked = check_schema_snapshot(&dir, test, &42u64);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(checked, Ok(()));
+        assert_eq!(check_schema_snapshot_from_str(None, &42u64), Ok(()));
+        assert!(check_schema_snapshot_from_str(Some("invalid"), &42u64).is_err());
+    }
+
+    #[test]
+    fn auxiliary_format_is_read_from_the_extension() {
+        let dir = temp_dir("load_auxiliary");
+        let mut serialized = Vec::new();
+        ciborium::ser::into_writer(&42u64, &mut serialized).unwrap();
+        for filename in ["key.cbor", "key.dat", "key"] {
+            fs::write(dir.join(filename), &serialized).unwrap();
+        }
+
+        let cbor = load_versioned_auxiliary::<u64, _>(dir.join("key.cbor"));
+        let unknown = load_versioned_auxiliary::<u64, _>(dir.join("key.dat"));
+        let missing = load_versioned_auxiliary::<u64, _>(dir.join("key"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(cbor, Ok(42));
+        assert!(unknown.unwrap_err().contains("unknown data format"));
+        assert!(missing.unwrap_err().contains("unknown data format"));
+    }
 }
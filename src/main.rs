@@ -1,57 +1,326 @@
-use tfhe_backward_compat_data::{
-    data_0_6::V0_6,
-    data_dir,
-    generate::{store_metadata, TfhersVersion},
-    Testcase, HL_MODULE_NAME, SHORTINT_MODULE_NAME,
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{self, exit, Command},
 };
 
-const PRNG_SEED: u128 = 0xdeadbeef;
+use tfhe_backward_compat_data::{
+    check_references, check_unique_testcases, data_dir,
+    diff::{diff_cbor_files, diff_versions, TestcaseDiff},
+    generate::{backfill_schema_snapshots, backfill_version_tags, store_metadata, GenerationArgs},
+    inspect::inspect_cbor_file,
+    metadata::{
+        json_metadata_filename, metadata_json_schema, metadata_to_json, parse_supported_metadata,
+        MetadataFile, METADATA_JSON_SCHEMA_FILE,
+    },
+    verify::verify_data_dir,
+    Testcase, METADATA_FILES,
+};
 
-fn gen_all_data<Vers: TfhersVersion>() -> Vec<Testcase> {
-    Vers::seed_prng(PRNG_SEED);
+/// The generator crate of each tfhe-rs version, relative to the root of this project.
+/// When we add more versions, extend this list with their crates.
+const VERSION_GENERATORS: &[(&str, &str)] = &[("0.6", "crates/generate_0_6")];
 
-    let shortint_tests = Vers::gen_shortint_data();
+const USAGE: &str = "Usage: tfhe-backward-compat-data [OPTIONS]
+       tfhe-backward-compat-data verify [DATA_DIR]
+       tfhe-backward-compat-data backfill [DATA_DIR]
+       tfhe-backward-compat-data inspect <CBOR_FILE>
+       tfhe-backward-compat-data diff <OLD_CBOR_FILE> <NEW_CBOR_FILE>
+       tfhe-backward-compat-data diff <OLD_VERSION> <NEW_VERSION>";
 
-    let mut tests: Vec<Testcase> = shortint_tests
+/// Loads the metadata that was previously generated, to keep the testcases of the versions that
+/// are not generated again
+fn load_previous_testcases(data_dir: &Path) -> Vec<Testcase> {
+    METADATA_FILES
         .iter()
-        .map(|metadata| Testcase {
-            tfhe_version_min: Vers::VERSION_NUMBER.to_string(),
-            tfhe_module: SHORTINT_MODULE_NAME.to_string(),
-            metadata: metadata.clone(),
+        .filter_map(|(_, filename)| fs::read_to_string(data_dir.join(filename)).ok())
+        .flat_map(|serialized| {
+            parse_supported_metadata(&serialized).unwrap_or_else(|e| {
+                eprintln!("Invalid test metadata: {}", e);
+                exit(1)
+            })
         })
-        .collect();
+        .collect()
+}
 
-    let hl_tests = Vers::gen_hl_data();
+/// Writes the metadata of the `testcases` of each module, in ron and in JSON, and the JSON Schema
+/// of the metadata
+fn store_all_metadata(data_dir: &Path, testcases: &[Testcase]) {
+    for (module, filename) in METADATA_FILES {
+        let module_testcases: Vec<Testcase> = testcases
+            .iter()
+            .filter(|test| test.tfhe_module == module)
+            .cloned()
+            .collect();
 
-    tests.extend(hl_tests.iter().map(|metadata| Testcase {
-        tfhe_version_min: Vers::VERSION_NUMBER.to_string(),
-        tfhe_module: HL_MODULE_NAME.to_string(),
-        metadata: metadata.clone(),
-    }));
+        store_metadata(
+            &MetadataFile::new(&module_testcases),
+            data_dir.join(filename),
+        );
+        // The JSON export is written with the ron metadata so that they never get out of sync
+        fs::write(
+            data_dir.join(json_metadata_filename(filename)),
+            metadata_to_json(&module_testcases),
+        )
+        .unwrap();
+    }
 
-    tests
+    fs::write(
+        data_dir.join(METADATA_JSON_SCHEMA_FILE),
+        metadata_json_schema(),
+    )
+    .unwrap();
 }
 
-fn main() {
-    let root_dir = env!("CARGO_MANIFEST_DIR");
-    let testcases = gen_all_data::<V0_6>(); // When we add more versions, extend the Vec with all the testcases
+/// Runs the generator crate of a version, and returns the metadata of all its testcases
+fn run_generator(
+    root_dir: &Path,
+    version: &str,
+    crate_dir: &str,
+    args: &GenerationArgs,
+) -> Vec<Testcase> {
+    let metadata_output = env::temp_dir().join(format!(
+        "tfhe_backward_compat_metadata_{}_{}.ron",
+        version.replace('.', "_"),
+        process::id()
+    ));
 
-    let shortint_testcases: Vec<Testcase> = testcases
-        .iter()
-        .filter(|test| test.tfhe_module == SHORTINT_MODULE_NAME)
-        .cloned()
-        .collect();
+    let mut version_args = args.clone();
+    version_args.metadata_output = Some(metadata_output.clone());
+
+    let manifest_path: PathBuf = root_dir.join(crate_dir).join("Cargo.toml");
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+
+    let status = Command::new(cargo)
+        .arg("run")
+        .arg("--release")
+        .arg("--manifest-path")
+        .arg(&manifest_path)
+        .arg("--")
+        .args(version_args.to_cli_args())
+        .current_dir(root_dir)
+        .status()
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to run the generator for tfhe-rs {}: {}", version, e);
+            exit(1)
+        });
 
-    store_metadata(&shortint_testcases, data_dir(root_dir).join("shortint.ron"));
+    if !status.success() {
+        eprintln!("The generator for tfhe-rs {} failed: {}", version, status);
+        exit(1)
+    }
 
-    let high_level_api_testcases: Vec<Testcase> = testcases
+    let serialized = fs::read_to_string(&metadata_output).unwrap_or_else(|e| {
+        eprintln!("Failed to read the metadata for tfhe-rs {}: {}", version, e);
+        exit(1)
+    });
+    let _ = fs::remove_file(&metadata_output);
+
+    parse_supported_metadata(&serialized).unwrap_or_else(|e| {
+        eprintln!("Invalid metadata for tfhe-rs {}: {}", version, e);
+        exit(1)
+    })
+}
+
+/// Checks that the metadata and the files in the data directory are consistent, and exits with
+/// an error if they are not
+fn verify(data_dir: &Path) -> ! {
+    let known_versions: Vec<&str> = VERSION_GENERATORS
         .iter()
-        .filter(|test| test.tfhe_module == HL_MODULE_NAME)
-        .cloned()
+        .map(|(version, _)| *version)
         .collect();
 
-    store_metadata(
-        &high_level_api_testcases,
-        data_dir(root_dir).join("high_level_api.ron"),
-    );
+    let problems = verify_data_dir(data_dir, &known_versions).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(1)
+    });
+
+    for problem in &problems {
+        println!("{}", problem);
+    }
+
+    let errors = problems.iter().filter(|problem| problem.is_error()).count();
+    let missing_records = problems.len() - errors;
+    if missing_records > 0 {
+        println!(
+            "{} missing record(s) in {}, their checks are skipped",
+            missing_records,
+            data_dir.display()
+        );
+    }
+
+    if errors == 0 {
+        println!("The data in {} are consistent", data_dir.display());
+        exit(0)
+    } else {
+        println!("{} problem(s) found in {}", errors, data_dir.display());
+        exit(1)
+    }
+}
+
+/// Extracts the schema snapshots and the version tags that are missing from the stored cbor
+/// data, for the testcases that cannot be generated again
+fn backfill(data_dir: &Path) -> ! {
+    let mut testcases = load_previous_testcases(data_dir);
+
+    let written = backfill_schema_snapshots(data_dir, &testcases).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(1)
+    });
+    for path in &written {
+        println!("Wrote {}", path.display());
+    }
+    println!("{} schema snapshot(s) written", written.len());
+
+    let updated = backfill_version_tags(data_dir, &mut testcases).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(1)
+    });
+    if updated > 0 {
+        store_all_metadata(data_dir, &testcases);
+    }
+    println!("Version tags recorded for {} testcase(s)", updated);
+
+    exit(0)
+}
+
+/// Prints the structure of a cbor file
+fn inspect(path: &Path) -> ! {
+    // Bincode is not self-describing, so the type of the data is needed to inspect it
+    if path.extension().is_some_and(|ext| ext != "cbor") {
+        eprintln!(
+            "Only cbor files can be inspected from the command line, use \
+`inspect::inspect_bincode` with the type of the data for bincode files"
+        );
+        exit(1)
+    }
+
+    match inspect_cbor_file(path) {
+        Ok(tree) => {
+            println!("{}", tree);
+            exit(0)
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(1)
+        }
+    }
+}
+
+/// Prints the structural differences between two cbor files, or between the data of two versions,
+/// and exits with an error if they differ
+fn diff(data_dir: &Path, old: &str, new: &str) -> ! {
+    let is_file = |arg: &str| Path::new(arg).extension().is_some_and(|ext| ext == "cbor");
+
+    let diffs = if is_file(old) && is_file(new) {
+        diff_cbor_files(old, new).map(|changes| {
+            if changes.is_empty() {
+                Vec::new()
+            } else {
+                vec![(
+                    format!("{} -> {}", old, new),
+                    TestcaseDiff::Changed(changes),
+                )]
+            }
+        })
+    } else {
+        diff_versions(data_dir, old, new)
+    }
+    .unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(1)
+    });
+
+    for (name, diff) in &diffs {
+        match diff {
+            TestcaseDiff::Added => println!("{}: added", name),
+            TestcaseDiff::Removed => println!("{}: removed", name),
+            TestcaseDiff::Changed(changes) => {
+                println!("{}:", name);
+                for change in changes {
+                    println!("  {}", change);
+                }
+            }
+        }
+    }
+
+    if diffs.is_empty() {
+        println!("No structural difference");
+        exit(0)
+    } else {
+        exit(1)
+    }
+}
+
+fn main() {
+    let root_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    let mut cli_args = env::args().skip(1).peekable();
+    match cli_args.peek().map(String::as_str) {
+        Some("verify") => {
+            cli_args.next();
+            let data_dir = cli_args
+                .next()
+                .map_or_else(|| data_dir(root_dir), PathBuf::from);
+            verify(&data_dir)
+        }
+        Some("backfill") => {
+            cli_args.next();
+            let data_dir = cli_args
+                .next()
+                .map_or_else(|| data_dir(root_dir), PathBuf::from);
+            backfill(&data_dir)
+        }
+        Some("inspect") => {
+            cli_args.next();
+            let Some(path) = cli_args.next() else {
+                eprintln!("Missing file to inspect\n\n{}", USAGE);
+                exit(1)
+            };
+            inspect(Path::new(&path))
+        }
+        Some("diff") => {
+            cli_args.next();
+            let (Some(old), Some(new)) = (cli_args.next(), cli_args.next()) else {
+                eprintln!("Missing files or versions to compare\n\n{}", USAGE);
+                exit(1)
+            };
+            diff(&data_dir(root_dir), &old, &new)
+        }
+        _ => {}
+    }
+
+    let args = GenerationArgs::parse(cli_args).unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}\n\n{}", e, USAGE, GenerationArgs::USAGE);
+        exit(1)
+    });
+
+    if args.help {
+        println!("{}\n\n{}", USAGE, GenerationArgs::USAGE);
+        return;
+    }
+
+    let data_dir = data_dir(root_dir);
+    let previous_testcases = load_previous_testcases(&data_dir);
+
+    let mut testcases: Vec<Testcase> = Vec::new();
+    for (version, crate_dir) in VERSION_GENERATORS {
+        if args.selects_version(version) {
+            testcases.extend(run_generator(root_dir, version, crate_dir, &args));
+        } else {
+            testcases.extend(
+                previous_testcases
+                    .iter()
+                    .filter(|test| test.tfhe_version_min == *version)
+                    .cloned(),
+            );
+        }
+    }
+
+    if let Err(e) = check_unique_testcases(&testcases).and_then(|_| check_references(&testcases)) {
+        eprintln!("Invalid testcases:\n{}", e);
+        exit(1)
+    }
+
+    store_all_metadata(&data_dir, &testcases);
 }
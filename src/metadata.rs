@@ -0,0 +1,616 @@
+#[cfg(feature = "generate")]
+use std::collections::BTreeMap;
+use std::{collections::BTreeSet, fmt};
+
+#[cfg(feature = "generate")]
+use schemars::JsonSchema;
+use serde::{
+    de::{
+        self, DeserializeSeed, EnumAccess, IgnoredAny, IntoDeserializer, MapAccess, VariantAccess,
+        Visitor,
+    },
+    forward_to_deserialize_any, Deserialize, Deserializer, Serialize,
+};
+
+#[cfg(feature = "generate")]
+use crate::TestcaseId;
+use crate::{StoredType, TestMetadata, Testcase, UnsupportedTest, VersionTags};
+
+/// Version of the layout of the metadata files written by this crate:
+/// - 1: a bare list of testcases
+/// - 2: a struct with the `schema_version` and the list of `testcases`
+pub const METADATA_SCHEMA_VERSION: u32 = 2;
+
+/// The file in the data directory that holds the JSON Schema of the JSON metadata files
+pub const METADATA_JSON_SCHEMA_FILE: &str = "metadata.schema.json";
+
+/// The content of a metadata file, in the latest layout
+#[derive(Serialize)]
+#[cfg_attr(feature = "generate", derive(JsonSchema))]
+pub struct MetadataFile<'a> {
+    pub schema_version: u32,
+    pub testcases: &'a [Testcase],
+}
+
+impl<'a> MetadataFile<'a> {
+    pub fn new(testcases: &'a [Testcase]) -> Self {
+        Self {
+            schema_version: METADATA_SCHEMA_VERSION,
+            testcases,
+        }
+    }
+}
+
+/// A metadata file whose testcases have been parsed with the layout of its schema version
+struct RawMetadata {
+    schema_version: u32,
+    testcases: Vec<Testcase>,
+}
+
+/// A metadata file written with a struct layout (schema version 2 and later). The testcases are
+/// first read as [`IgnoredAny`], so that the schema version is checked before they are parsed.
+#[derive(Deserialize)]
+struct VersionedMetadata<T> {
+    schema_version: u32,
+    testcases: T,
+}
+
+/// Reads the schema version of a metadata file, without parsing the testcases
+fn read_schema_version(serialized: &str) -> Result<u32, String> {
+    // The schema version 1 is a bare list of testcases, without header
+    if ron::from_str::<Vec<IgnoredAny>>(serialized).is_ok() {
+        return Ok(1);
+    }
+
+    ron::from_str::<VersionedMetadata<IgnoredAny>>(serialized)
+        .map(|metadata| metadata.schema_version)
+        .map_err(|e| e.to_string())
+}
+
+/// Parses the testcases of a metadata file with the layout of its schema version
+fn read_layout(serialized: &str, schema_version: u32) -> Result<RawMetadata, String> {
+    let testcases = match schema_version {
+        1 => ron::from_str::<Vec<TestcaseEntry>>(serialized),
+        _ => ron::from_str::<VersionedMetadata<Vec<TestcaseEntry>>>(serialized)
+            .map(|metadata| metadata.testcases),
+    }
+    .map_err(|e| e.to_string())?;
+
+    Ok(RawMetadata {
+        schema_version,
+        testcases: testcases.into_iter().map(Testcase::from).collect(),
+    })
+}
+
+/// Upgrades the metadata from the layout of its schema version to the layout of the next version
+fn migrate(raw: RawMetadata) -> Result<RawMetadata, String> {
+    match raw.schema_version {
+        // The bare list was wrapped in a struct to hold the schema version, the testcases did not
+        // change
+        1 => Ok(RawMetadata {
+            schema_version: 2,
+            testcases: raw.testcases,
+        }),
+        version => Err(format!("no migration from schema version {}", version)),
+    }
+}
+
+/// A testcase as written in the metadata files, whose metadata are parsed leniently
+#[derive(Deserialize)]
+struct TestcaseEntry {
+    tfhe_version_min: String,
+    tfhe_module: String,
+    metadata: LenientMetadata,
+    #[serde(default)]
+    version_tags: Option<VersionTags>,
+    #[serde(default)]
+    stored_type: Option<StoredType>,
+    #[serde(default)]
+    tags: BTreeSet<String>,
+}
+
+impl From<TestcaseEntry> for Testcase {
+    fn from(entry: TestcaseEntry) -> Self {
+        Self {
+            tfhe_version_min: entry.tfhe_version_min,
+            tfhe_module: entry.tfhe_module,
+            metadata: entry.metadata.0,
+            version_tags: entry.version_tags,
+            stored_type: entry.stored_type,
+            tags: entry.tags,
+        }
+    }
+}
+
+/// The test filename of a testcase whose kind is unknown
+#[derive(Deserialize)]
+struct TestFilename {
+    test_filename: String,
+}
+
+/// The name of a kind of test or of a field, that may be unknown
+struct Identifier(String);
+
+impl<'de> Deserialize<'de> for Identifier {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IdentifierVisitor;
+
+        impl<'de> Visitor<'de> for IdentifierVisitor {
+            type Value = Identifier;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an identifier")
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<Self::Value, E> {
+                Ok(Identifier(name.to_string()))
+            }
+        }
+
+        deserializer.deserialize_identifier(IdentifierVisitor)
+    }
+}
+
+/// The metadata of a testcase. Only the kinds and fields added by later versions of the crate make
+/// a testcase unsupported: they are returned as [`TestMetadata::Unsupported`] instead of an error.
+struct LenientMetadata(TestMetadata);
+
+impl<'de> Deserialize<'de> for LenientMetadata {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MetadataVisitor;
+
+        impl<'de> Visitor<'de> for MetadataVisitor {
+            type Value = LenientMetadata;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a kind of test")
+            }
+
+            fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+                let (Identifier(kind), variant) = data.variant()?;
+                let mut variant = Some(variant);
+                let mut unknown_fields = Vec::new();
+
+                let metadata = TestMetadata::deserialize(KnownKind {
+                    kind: &kind,
+                    variant: &mut variant,
+                    unknown_fields: &mut unknown_fields,
+                });
+
+                let (test_filename, reason) = match (metadata, variant) {
+                    // The variant is only read if its kind is known
+                    (Err(e), Some(variant)) => {
+                        let TestFilename { test_filename } = variant.newtype_variant()?;
+                        (test_filename, e.to_string())
+                    }
+                    (Err(e), None) => return Err(e),
+                    (Ok(metadata), _) if !unknown_fields.is_empty() => (
+                        metadata.test_filename(),
+                        format!("unknown fields {}", unknown_fields.join(", ")),
+                    ),
+                    (Ok(metadata), _) => return Ok(LenientMetadata(metadata)),
+                };
+
+                Ok(LenientMetadata(TestMetadata::Unsupported(
+                    UnsupportedTest {
+                        test_filename: test_filename.into(),
+                        kind,
+                        reason,
+                    },
+                )))
+            }
+        }
+
+        deserializer.deserialize_enum("TestMetadata", &[], MetadataVisitor)
+    }
+}
+
+/// Gives the kind of a testcase, that has already been read, to the deserializer of
+/// [`TestMetadata`]. Its variant is only taken once the kind is known to be supported, and its
+/// fields that are not supported are skipped and recorded in `unknown_fields`.
+struct KnownKind<'a, A> {
+    kind: &'a str,
+    variant: &'a mut Option<A>,
+    unknown_fields: &'a mut Vec<String>,
+}
+
+impl<'de, 'a, A: VariantAccess<'de>> Deserializer<'de> for KnownKind<'a, A> {
+    type Error = A::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+impl<'de, 'a, A: VariantAccess<'de>> EnumAccess<'de> for KnownKind<'a, A> {
+    type Error = A::Error;
+    type Variant = KnownFields<'a, A>;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Self::Variant), Self::Error> {
+        let kind = seed.deserialize(self.kind.into_deserializer())?;
+        let variant = self.variant.take().expect("the variant is only read once");
+
+        Ok((
+            kind,
+            KnownFields {
+                inner: variant,
+                fields: &[],
+                unknown_fields: self.unknown_fields,
+            },
+        ))
+    }
+}
+
+/// Wraps the variant of a known kind, its content or the access to the fields of its struct, to
+/// skip the fields that are not in `fields` and record them in `unknown_fields`
+struct KnownFields<'a, T> {
+    inner: T,
+    fields: &'static [&'static str],
+    unknown_fields: &'a mut Vec<String>,
+}
+
+impl<'de, 'a, A: VariantAccess<'de>> VariantAccess<'de> for KnownFields<'a, A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<S::Value, Self::Error> {
+        let KnownFields {
+            inner,
+            fields,
+            unknown_fields,
+        } = self;
+        inner.newtype_variant_seed(KnownFields {
+            inner: seed,
+            fields,
+            unknown_fields,
+        })
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.tuple_variant(len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.struct_variant(fields, visitor)
+    }
+}
+
+impl<'de, 'a, S: DeserializeSeed<'de>> DeserializeSeed<'de> for KnownFields<'a, S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let KnownFields {
+            inner,
+            fields,
+            unknown_fields,
+        } = self;
+        inner.deserialize(KnownFields {
+            inner: deserializer,
+            fields,
+            unknown_fields,
+        })
+    }
+}
+
+impl<'de, 'a, D: Deserializer<'de>> Deserializer<'de> for KnownFields<'a, D> {
+    type Error = D::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.inner.deserialize_any(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let KnownFields {
+            inner,
+            unknown_fields,
+            ..
+        } = self;
+        inner.deserialize_struct(
+            name,
+            fields,
+            KnownFields {
+                inner: visitor,
+                fields,
+                unknown_fields,
+            },
+        )
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+impl<'de, 'a, V: Visitor<'de>> Visitor<'de> for KnownFields<'a, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, map: M) -> Result<Self::Value, M::Error> {
+        let KnownFields {
+            inner,
+            fields,
+            unknown_fields,
+        } = self;
+        inner.visit_map(KnownFields {
+            inner: map,
+            fields,
+            unknown_fields,
+        })
+    }
+}
+
+impl<'de, 'a, M: MapAccess<'de>> MapAccess<'de> for KnownFields<'a, M> {
+    type Error = M::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        while let Some(Identifier(field)) = self.inner.next_key()? {
+            if self.fields.contains(&field.as_str()) {
+                return seed.deserialize(field.into_deserializer()).map(Some);
+            }
+
+            self.inner.next_value::<IgnoredAny>()?;
+            self.unknown_fields.push(format!("`{}`", field));
+        }
+
+        Ok(None)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<S::Value, Self::Error> {
+        self.inner.next_value_seed(seed)
+    }
+}
+
+/// Parses the content of a metadata file, written in the layout of any schema version up to
+/// [`METADATA_SCHEMA_VERSION`].
+///
+/// The testcases that this version of the crate does not know are returned as
+/// [`TestMetadata::Unsupported`], so that the others can still be run.
+pub fn parse_metadata(serialized: &str) -> Result<Vec<Testcase>, String> {
+    let schema_version =
+        read_schema_version(serialized).map_err(|e| format!("Invalid metadata: {}", e))?;
+
+    if schema_version > METADATA_SCHEMA_VERSION {
+        return Err(format!(
+            "Metadata schema version {} is not supported, the latest supported version is {}. \
+            Please update tfhe-backward-compat-data.",
+            schema_version, METADATA_SCHEMA_VERSION
+        ));
+    }
+
+    let mut raw =
+        read_layout(serialized, schema_version).map_err(|e| format!("Invalid metadata: {}", e))?;
+    while raw.schema_version < METADATA_SCHEMA_VERSION {
+        raw = migrate(raw).map_err(|e| format!("Invalid metadata: {}", e))?;
+    }
+
+    Ok(raw.testcases)
+}
+
+/// Same as [`parse_metadata`], but returns an error if some testcases are not supported. This
+/// should be used by the tools that write the metadata back, so that they never lose testcases.
+pub fn parse_supported_metadata(serialized: &str) -> Result<Vec<Testcase>, String> {
+    let testcases = parse_metadata(serialized)?;
+
+    let errors: Vec<String> = testcases
+        .iter()
+        .filter_map(|testcase| match &testcase.metadata {
+            TestMetadata::Unsupported(test) => Some(format!(
+                "Unsupported testcase {} of kind {}: {}",
+                testcase.id(),
+                test.kind,
+                test.reason
+            )),
+            _ => None,
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(testcases)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// The name of the JSON export of a metadata file (eg: `shortint.json` for `shortint.ron`)
+pub fn json_metadata_filename(filename: &str) -> String {
+    format!("{}.json", filename.strip_suffix(".ron").unwrap_or(filename))
+}
+
+/// A metadata file exported in JSON, that is always written with the latest layout
+#[cfg(feature = "generate")]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonMetadataFile {
+    schema_version: u32,
+    testcases: Vec<Testcase>,
+}
+
+/// Exports the testcases of a metadata file in JSON, for the tools that are not written in rust.
+/// The layout is the same as the one of the ron files, and is described by
+/// [`metadata_json_schema`].
+#[cfg(feature = "generate")]
+pub fn metadata_to_json(testcases: &[Testcase]) -> String {
+    let mut serialized = serde_json::to_string_pretty(&MetadataFile::new(testcases)).unwrap();
+    serialized.push('\n');
+    serialized
+}
+
+/// Parses the JSON export of a metadata file
+#[cfg(feature = "generate")]
+pub fn parse_json_metadata(serialized: &str) -> Result<Vec<Testcase>, String> {
+    let metadata: JsonMetadataFile =
+        serde_json::from_str(serialized).map_err(|e| format!("Invalid JSON metadata: {}", e))?;
+
+    if metadata.schema_version != METADATA_SCHEMA_VERSION {
+        return Err(format!(
+            "JSON metadata written with schema version {}, expected version {}",
+            metadata.schema_version, METADATA_SCHEMA_VERSION
+        ));
+    }
+
+    Ok(metadata.testcases)
+}
+
+/// The JSON Schema of the JSON metadata files, derived from the metadata types
+#[cfg(feature = "generate")]
+pub fn metadata_json_schema() -> String {
+    let schema = schemars::schema_for!(MetadataFile);
+    let mut serialized = serde_json::to_string_pretty(&schema).unwrap();
+    serialized.push('\n');
+    serialized
+}
+
+/// Checks that the JSON export of a metadata file describes the same `testcases` as the ron file
+#[cfg(feature = "generate")]
+pub fn check_json_metadata(testcases: &[Testcase], json: &str) -> Result<(), String> {
+    // The testcases are compared through their ron serialization, that holds all their fields
+    let by_id = |testcases: &[Testcase]| -> BTreeMap<TestcaseId, String> {
+        testcases
+            .iter()
+            .map(|testcase| (testcase.id(), ron::to_string(testcase).unwrap()))
+            .collect()
+    };
+
+    let json_testcases = parse_json_metadata(json)?;
+    let expected = by_id(testcases);
+    let exported = by_id(&json_testcases);
+
+    let mut errors = Vec::new();
+    for (id, serialized) in &expected {
+        match exported.get(id) {
+            None => errors.push(format!("Testcase {} is missing from the JSON metadata", id)),
+            Some(exported) if exported != serialized => errors.push(format!(
+                "Testcase {} differs in the JSON metadata: expected {}, got {}",
+                id, serialized, exported
+            )),
+            Some(_) => {}
+        }
+    }
+    errors.extend(
+        exported
+            .keys()
+            .filter(|id| !expected.contains_key(*id))
+            .map(|id| format!("Testcase {} is only in the JSON metadata", id)),
+    );
+
+    if json_testcases.len() != testcases.len() && errors.is_empty() {
+        errors.push(format!(
+            "The JSON metadata has {} testcases, expected {}",
+            json_testcases.len(),
+            testcases.len()
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::hl_ciphertext;
+
+    fn parse_testcase(serialized: &str) -> Result<Testcase, ron::error::SpannedError> {
+        ron::from_str::<TestcaseEntry>(serialized).map(Testcase::from)
+    }
+
+    #[test]
+    fn bare_lists_are_migrated() {
+        let ct = hl_ciphertext("0.6", "ct", "client_key");
+        let bare = format!("[{},]", ct);
+        assert_eq!(read_schema_version(&bare).unwrap(), 1);
+        let raw = read_layout(&bare, 1).unwrap();
+
+        let migrated = migrate(raw).unwrap();
+        assert_eq!(migrated.schema_version, 2);
+        assert_eq!(migrated.testcases.len(), 1);
+
+        let testcases = parse_metadata(&bare).unwrap();
+        assert_eq!(testcases.len(), 1);
+        assert!(matches!(
+            testcases[0].metadata,
+            TestMetadata::HlCiphertext(_)
+        ));
+    }
+
+    #[test]
+    fn unknown_kinds_and_fields_are_unsupported() {
+        let ct = hl_ciphertext("0.6", "ct", "client_key");
+        let unknown_kind = ct.replace("HlCiphertext(", "HlFutureCiphertext(");
+        let unknown_field = ct.replace("compact:false,", "compact:false,packed:true,");
+
+        for (serialized, reason) in [
+            (
+                unknown_kind,
+                "Unexpected enum variant named `HlFutureCiphertext`",
+            ),
+            (unknown_field, "unknown fields `packed`"),
+        ] {
+            let testcase = parse_testcase(&serialized).unwrap();
+            assert_eq!(testcase.metadata.module(), None);
+            let TestMetadata::Unsupported(test) = testcase.metadata else {
+                panic!("{} should be unsupported", serialized);
+            };
+            assert_eq!(test.test_filename, "ct");
+            assert!(test.reason.starts_with(reason), "{}", test.reason);
+            assert_eq!(testcase.tags, BTreeSet::from(["fast".to_string()]));
+        }
+    }
+
+    #[test]
+    fn invalid_testcases_are_errors() {
+        let ct = hl_ciphertext("0.6", "ct", "client_key");
+        let out_of_range = ct.replace("value:255", "value:300");
+        let wrong_type = ct.replace("compressed:false", "compressed:0");
+        let missing_field = ct.replace("compact:false,", "");
+
+        for serialized in [out_of_range, wrong_type, missing_field] {
+            assert!(
+                parse_testcase(&serialized).is_err(),
+                "{} should be invalid",
+                serialized
+            );
+        }
+    }
+}
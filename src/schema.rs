@@ -0,0 +1,269 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    inspect::{inspect_value, is_version_tag, InspectNode},
+    VersionTags,
+};
+
+pub use crate::layout::{schema_path, SCHEMA_EXTENSION};
+
+/// How the root of the object is named in the errors
+const ROOT_PATH: &str = "<root>";
+
+/// Path of the field `name` of the value at `path`
+fn child_path(path: &str, name: &str) -> String {
+    if path == ROOT_PATH {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+/// The layout of a versioned object, without its data: the nesting of the types, the names of
+/// their fields and the version tags.
+///
+/// The snapshot is extracted from what is visible in a cbor file, so the integer sizes and the
+/// names of the unit enum variants are not recorded.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Schema {
+    /// A versioned type, with its version tag (eg: `V0`)
+    Versioned {
+        tag: String,
+        inner: Box<Schema>,
+    },
+    /// A struct, a map or an enum variant, with its fields in the serialization order
+    Struct(Vec<(String, Schema)>),
+    /// An array, with the schema of its items if it is not empty
+    Array(Option<Box<Schema>>),
+    Bytes,
+    Integer,
+    Float,
+    Bool,
+    Text,
+    Null,
+}
+
+impl Schema {
+    pub fn from_node(node: &InspectNode) -> Self {
+        match node {
+            InspectNode::Versioned { tag, value } => Self::Versioned {
+                tag: tag.clone(),
+                inner: Box::new(Self::from_node(value)),
+            },
+            InspectNode::Map(fields) => Self::Struct(
+                fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), Self::from_node(value)))
+                    .collect(),
+            ),
+            InspectNode::Seq { items, .. } => {
+                Self::Array(items.first().map(|item| Box::new(Self::from_node(item))))
+            }
+            InspectNode::Bytes { .. } => Self::Bytes,
+            InspectNode::Integer(_) => Self::Integer,
+            InspectNode::Float(_) => Self::Float,
+            InspectNode::Bool(_) => Self::Bool,
+            InspectNode::Text(_) => Self::Text,
+            InspectNode::Null => Self::Null,
+        }
+    }
+
+    /// Extracts the schema of a versioned object
+    pub fn of_value<Data: Serialize>(versioned: &Data) -> Result<Self, String> {
+        inspect_value(versioned).map(|node| Self::from_node(&node))
+    }
+
+    /// Lists the version tags in this schema, or returns `None` if the object itself is not
+    /// versioned. Nested tags are identified by their path in the object.
+    pub fn version_tags(&self) -> Option<VersionTags> {
+        let Self::Versioned { tag, inner } = self else {
+            return None;
+        };
+
+        let mut nested = BTreeMap::new();
+        inner.collect_tags(ROOT_PATH, &mut nested);
+
+        Some(VersionTags {
+            top_level: tag.clone(),
+            nested,
+        })
+    }
+
+    fn collect_tags(&self, path: &str, tags: &mut BTreeMap<String, String>) {
+        match self {
+            Self::Versioned { tag, inner } => {
+                tags.insert(path.to_string(), tag.clone());
+                inner.collect_tags(path, tags);
+            }
+            Self::Struct(fields) => {
+                for (name, schema) in fields {
+                    schema.collect_tags(&child_path(path, name), tags);
+                }
+            }
+            Self::Array(Some(item)) => item.collect_tags(&format!("{}[]", path), tags),
+            _ => {}
+        }
+    }
+
+    fn kind_name(&self) -> &'static str {
+        match self {
+            Self::Versioned { .. } => "versioned type",
+            Self::Struct(_) => "struct",
+            Self::Array(_) => "array",
+            Self::Bytes => "bytes",
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::Bool => "bool",
+            Self::Text => "text",
+            Self::Null => "null",
+        }
+    }
+
+    fn check_at(&self, path: &str, current: &Schema, errors: &mut Vec<String>) {
+        match (self, current) {
+            (
+                Self::Versioned { tag, inner },
+                Self::Versioned {
+                    tag: current_tag,
+                    inner: current_inner,
+                },
+            ) => {
+                // If the version changed, the upgrade path from the old layout is handled by
+                // the versioning, so the old and new layouts are allowed to differ. Only the
+                // dispatch tags of the versioning (`V0`, `V1`, ...) mark a new version.
+                if tag == current_tag {
+                    inner.check_at(path, current_inner, errors);
+                } else if !is_version_tag(tag) || !is_version_tag(current_tag) {
+                    errors.push(format!(
+                        "{}: variant changed from {} to {}",
+                        path, tag, current_tag
+                    ));
+                }
+            }
+            (Self::Struct(fields), Self::Struct(current_fields)) => {
+                let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+                let current_names: Vec<&str> = current_fields
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect();
+
+                if names != current_names {
+                    errors.push(format!(
+                        "{}: fields changed from ({}) to ({})",
+                        path,
+                        names.join(", "),
+                        current_names.join(", ")
+                    ));
+                    return;
+                }
+
+                for ((name, schema), (_, current_schema)) in fields.iter().zip(current_fields) {
+                    schema.check_at(&child_path(path, name), current_schema, errors);
+                }
+            }
+            (Self::Array(item), Self::Array(current_item)) => {
+                // Nothing can be compared for empty arrays
+                if let (Some(item), Some(current_item)) = (item, current_item) {
+                    item.check_at(&format!("{}[]", path), current_item, errors);
+                }
+            }
+            (snapshot, current) => {
+                if snapshot.kind_name() != current.kind_name() {
+                    errors.push(format!(
+                        "{}: changed from {} to {}",
+                        path,
+                        snapshot.kind_name(),
+                        current.kind_name()
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Checks that the `current` schema of a type, extracted from an object serialized with
+    /// the current tfhe-rs, matches this snapshot.
+    ///
+    /// The layout of a versioned type is only allowed to change if its version tag changed too:
+    /// the parts of the schema under a different version tag (eg: `V0` and `V1`) are not
+    /// compared.
+    pub fn check_compatible(&self, current: &Schema) -> Result<(), String> {
+        let mut errors = Vec::new();
+        self.check_at(ROOT_PATH, current, &mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Layout changed without a new version: {}",
+                errors.join("; ")
+            ))
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let serialized = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read schema {}: {}", path.display(), e))?;
+        Self::parse(&serialized).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Parses the content of a schema snapshot
+    pub fn parse(serialized: &str) -> Result<Self, String> {
+        ron::from_str(serialized).map_err(|e| format!("Invalid schema: {}", e))
+    }
+
+    pub fn store<P: AsRef<Path>>(&self, path: P) {
+        let serialized =
+            ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).unwrap();
+        fs::write(path, serialized).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versioned(tag: &str, inner: Schema) -> Schema {
+        Schema::Versioned {
+            tag: tag.to_string(),
+            inner: Box::new(inner),
+        }
+    }
+
+    fn fields(names: &[&str]) -> Schema {
+        Schema::Struct(
+            names
+                .iter()
+                .map(|name| (name.to_string(), Schema::Integer))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn new_versions_may_change_the_layout() {
+        let snapshot = versioned("V0", fields(&["a"]));
+
+        snapshot
+            .check_compatible(&versioned("V1", fields(&["a", "b"])))
+            .unwrap();
+        assert!(snapshot
+            .check_compatible(&versioned("V0", fields(&["a", "b"])))
+            .is_err());
+    }
+
+    #[test]
+    fn other_tags_are_not_version_boundaries() {
+        let snapshot = versioned("Big", fields(&["a"]));
+
+        let error = snapshot
+            .check_compatible(&versioned("Small", fields(&["a"])))
+            .unwrap_err();
+        assert!(
+            error.contains("variant changed from Big to Small"),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,37 @@
+//! Fixtures shared by the unit tests of the modules of the crate
+
+use std::{env, fs, path::PathBuf, process};
+
+use crate::{metadata::parse_metadata, Testcase};
+
+/// An empty directory in the temporary directory, unique to the test `name` and to this process
+pub(crate) fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("{}_{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// The ron metadata of a high level api ciphertext of tfhe-rs `version`, that references the
+/// client key `key_filename`
+pub(crate) fn hl_ciphertext(version: &str, test_filename: &str, key_filename: &str) -> String {
+    format!(
+        r#"(tfhe_version_min:{:?},tfhe_module:"high_level_api",metadata:HlCiphertext((test_filename:{:?},key_filename:{:?},compressed:false,compact:false,clear_value:(value:255,num_bits:8,signed:false))),tags:["fast"])"#,
+        version, test_filename, key_filename
+    )
+}
+
+/// The ron metadata of the high level api client key `client_key` of tfhe-rs 0.6, with its recorded
+/// version tags and stored type
+pub(crate) const HL_CLIENT_KEY: &str = r#"(tfhe_version_min:"0.6",tfhe_module:"high_level_api",metadata:HlClientKey((test_filename:"client_key",parameters:(lwe_dimension:761,glwe_dimension:1,polynomial_size:2048,lwe_noise_gaussian_stddev:0.00000636835566258815,glwe_noise_gaussian_stddev:0.00000000000000031529322391500584,pbs_base_log:23,pbs_level:1,ks_base_log:3,ks_level:5,message_modulus:4,ciphertext_modulus:18446744073709551616,carry_modulus:4,max_noise_level:5,log2_p_fail:-40.05,encryption_key_choice:"big"))),version_tags:Some((top_level:"V0")),stored_type:Some((type_name:"tfhe::high_level_api::keys::client::ClientKey",construction:"ClientKey::generate")),tags:["fast"])"#;
+
+/// The content of a metadata file that holds the ron `testcases`
+pub(crate) fn metadata_file<S: AsRef<str>>(testcases: &[S]) -> String {
+    let testcases: Vec<&str> = testcases.iter().map(AsRef::as_ref).collect();
+    format!("(schema_version:2,testcases:[{}])", testcases.join(","))
+}
+
+/// Parses the ron `testcases`
+pub(crate) fn parse_testcases<S: AsRef<str>>(testcases: &[S]) -> Vec<Testcase> {
+    parse_metadata(&metadata_file(testcases)).unwrap()
+}
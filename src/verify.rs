@@ -0,0 +1,485 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    dir_for_version, load_all_metadata, schema::schema_path, TestMetadata, TestcaseId,
+    DATA_EXTENSIONS, MANIFEST_FILENAME,
+};
+#[cfg(feature = "generate")]
+use crate::{
+    metadata::{
+        check_json_metadata, json_metadata_filename, metadata_json_schema,
+        METADATA_JSON_SCHEMA_FILE,
+    },
+    Testcase, METADATA_FILES,
+};
+
+/// An inconsistency between the metadata of the testcases and the files in the data directory
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DataProblem {
+    /// A file referenced by the metadata does not exist, in one of the formats
+    MissingFile {
+        path: PathBuf,
+        referenced_by: Vec<String>,
+    },
+    /// A file in the data directory is not referenced by any testcase
+    OrphanFile { path: PathBuf },
+    /// Several testcases are stored in the same file
+    DuplicateTestcase { id: TestcaseId, count: usize },
+    /// A testcase references an auxiliary file that is not declared as a client key testcase
+    InvalidReference { error: String },
+    /// A testcase cannot be parsed by this version of the crate
+    UnsupportedTestcase {
+        id: TestcaseId,
+        kind: String,
+        reason: String,
+    },
+    /// The schema snapshot of a testcase was not recorded, its check is skipped
+    MissingSchemaSnapshot { path: PathBuf, id: TestcaseId },
+    /// No version tags are recorded for a testcase, its check is skipped
+    MissingVersionTags { id: TestcaseId },
+    /// The type of the stored object is not recorded for a testcase, its check is skipped
+    MissingStoredType { id: TestcaseId },
+    /// The JSON export of a metadata file is missing or does not describe the same testcases
+    OutdatedJsonMetadata { path: PathBuf, error: String },
+    /// The JSON Schema of the metadata is missing or was not derived from the current types
+    OutdatedJsonSchema { path: PathBuf },
+    /// Some testcases are defined for a version of tfhe-rs that is not known
+    UnknownVersion { version: String },
+    /// A directory in the data directory does not match any known version of tfhe-rs
+    UnknownVersionDir { path: PathBuf },
+}
+
+impl Display for DataProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingFile {
+                path,
+                referenced_by,
+            } => write!(
+                f,
+                "Missing file {}, referenced by {}",
+                path.display(),
+                referenced_by.join(", ")
+            ),
+            Self::OrphanFile { path } => write!(
+                f,
+                "Orphan file {}, not referenced by any testcase",
+                path.display()
+            ),
+            Self::DuplicateTestcase { id, count } => {
+                write!(f, "Duplicate testcase {}, defined {} times", id, count)
+            }
+            Self::InvalidReference { error } => write!(f, "{}", error),
+            Self::UnsupportedTestcase { id, kind, reason } => write!(
+                f,
+                "Unsupported testcase {} of kind {}: {}",
+                id, kind, reason
+            ),
+            Self::MissingSchemaSnapshot { path, id } => write!(
+                f,
+                "Schema snapshot {} of testcase {} not recorded, its check is skipped. Run \
+                `backfill` to extract it from the cbor data",
+                path.display(),
+                id
+            ),
+            Self::MissingVersionTags { id } => write!(
+                f,
+                "Version tags of testcase {} not recorded, their check is skipped. Run \
+                `backfill` to read them from the cbor data",
+                id
+            ),
+            Self::MissingStoredType { id } => write!(
+                f,
+                "Stored type of testcase {} not recorded, its check is skipped. Run the \
+                generator of its version to record it",
+                id
+            ),
+            Self::OutdatedJsonMetadata { path, error } => {
+                write!(f, "Outdated JSON metadata {}: {}", path.display(), error)
+            }
+            Self::OutdatedJsonSchema { path } => write!(
+                f,
+                "Outdated JSON Schema {}, it does not match the metadata types",
+                path.display()
+            ),
+            Self::UnknownVersion { version } => {
+                write!(
+                    f,
+                    "Testcases defined for unknown tfhe-rs version {}",
+                    version
+                )
+            }
+            Self::UnknownVersionDir { path } => write!(
+                f,
+                "Directory {} does not match any known tfhe-rs version",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl DataProblem {
+    /// Returns false for the records that are missing because the data were generated before
+    /// they were introduced (eg: a schema snapshot, the version tags or the stored type). They
+    /// are reported, but their checks are skipped and they do not make the data inconsistent.
+    pub fn is_error(&self) -> bool {
+        !matches!(
+            self,
+            Self::MissingSchemaSnapshot { .. }
+                | Self::MissingVersionTags { .. }
+                | Self::MissingStoredType { .. }
+        )
+    }
+}
+
+/// Lists all the files in `dir` and its sub-directories
+fn list_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read dir {}: {}", dir.display(), e))?;
+
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("Failed to read dir {}: {}", dir.display(), e))?
+            .path();
+
+        if path.is_dir() {
+            list_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the JSON exports of the metadata files and their JSON Schema are up to date
+#[cfg(feature = "generate")]
+fn check_json_exports(data_dir: &Path, testcases: &[Testcase]) -> Vec<DataProblem> {
+    let mut problems = Vec::new();
+
+    for (module, filename) in METADATA_FILES {
+        let module_testcases: Vec<Testcase> = testcases
+            .iter()
+            .filter(|testcase| testcase.tfhe_module == module)
+            .cloned()
+            .collect();
+
+        let path = data_dir.join(json_metadata_filename(filename));
+        let checked = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read file: {}", e))
+            .and_then(|json| check_json_metadata(&module_testcases, &json));
+
+        if let Err(error) = checked {
+            problems.push(DataProblem::OutdatedJsonMetadata { path, error });
+        }
+    }
+
+    let path = data_dir.join(METADATA_JSON_SCHEMA_FILE);
+    if fs::read_to_string(&path).ok() != Some(metadata_json_schema()) {
+        problems.push(DataProblem::OutdatedJsonSchema { path });
+    }
+
+    problems
+}
+
+/// Cross-checks the metadata stored in `data_dir` with the files of the testcases.
+///
+/// Every test file referenced by the metadata should exist in all the formats with its schema
+/// snapshot, every auxiliary file should be declared as a testcase, every file in the directories
+/// of the versions should belong to a testcase, and the testcases should only be defined for the
+/// `known_versions`. With the `generate` feature, the JSON exports of the metadata should also
+/// describe the same testcases as the ron files.
+///
+/// Returns an error if the metadata or the data directory cannot be read, and the list of all the
+/// problems that were found otherwise.
+pub fn verify_data_dir<P: AsRef<Path>>(
+    data_dir: P,
+    known_versions: &[&str],
+) -> Result<Vec<DataProblem>, String> {
+    let data_dir = data_dir.as_ref();
+    let testcases = load_all_metadata(data_dir)?;
+
+    let mut problems = Vec::new();
+    let mut unknown_versions = BTreeSet::new();
+    let mut ids: BTreeMap<TestcaseId, usize> = BTreeMap::new();
+    // All the files referenced by the metadata, with the testcases that reference them
+    let mut referenced_files: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+    let mut schema_files = BTreeSet::new();
+
+    for testcase in &testcases {
+        let version = &testcase.tfhe_version_min;
+        let module = &testcase.tfhe_module;
+        let test_filename = testcase.metadata.test_filename();
+
+        if !known_versions.contains(&version.as_str()) {
+            unknown_versions.insert(version.clone());
+        }
+
+        let id = testcase.id();
+        let testcase_name = id.to_string();
+        *ids.entry(id).or_default() += 1;
+
+        let module_dir = dir_for_version(data_dir, version).join(module);
+
+        // The auxiliary files are testcases too, so only the test files need to be listed
+        for extension in DATA_EXTENSIONS {
+            referenced_files
+                .entry(module_dir.join(format!("{}.{}", test_filename, extension)))
+                .or_default()
+                .push(testcase_name.clone());
+        }
+
+        let snapshot_path = schema_path(&module_dir, &test_filename);
+        if !snapshot_path.is_file() {
+            problems.push(DataProblem::MissingSchemaSnapshot {
+                path: snapshot_path.clone(),
+                id: testcase.id(),
+            });
+        }
+        schema_files.insert(snapshot_path);
+
+        if testcase.version_tags.is_none() {
+            problems.push(DataProblem::MissingVersionTags { id: testcase.id() });
+        }
+
+        if testcase.stored_type.is_none() {
+            problems.push(DataProblem::MissingStoredType { id: testcase.id() });
+        }
+
+        if let Err(error) = testcase.resolve_auxiliaries(&testcases) {
+            problems.push(DataProblem::InvalidReference { error });
+        }
+
+        if let TestMetadata::Unsupported(test) = &testcase.metadata {
+            problems.push(DataProblem::UnsupportedTestcase {
+                id: testcase.id(),
+                kind: test.kind.clone(),
+                reason: test.reason.clone(),
+            });
+        }
+    }
+
+    // The JSON exports of metadata written by a newer version of this crate cannot be checked,
+    // the unsupported testcases are already reported
+    #[cfg(feature = "generate")]
+    if !testcases
+        .iter()
+        .any(|testcase| matches!(testcase.metadata, TestMetadata::Unsupported(_)))
+    {
+        problems.extend(check_json_exports(data_dir, &testcases));
+    }
+
+    problems.extend(
+        unknown_versions
+            .into_iter()
+            .map(|version| DataProblem::UnknownVersion { version }),
+    );
+
+    problems.extend(
+        ids.into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(id, count)| DataProblem::DuplicateTestcase { id, count }),
+    );
+
+    problems.extend(
+        referenced_files
+            .iter()
+            .filter(|(path, _)| !path.is_file())
+            .map(|(path, referenced_by)| {
+                let mut referenced_by = referenced_by.clone();
+                referenced_by.dedup();
+                DataProblem::MissingFile {
+                    path: path.clone(),
+                    referenced_by,
+                }
+            }),
+    );
+
+    let known_dirs: Vec<PathBuf> = known_versions
+        .iter()
+        .map(|version| dir_for_version(data_dir, version))
+        .collect();
+
+    let entries = fs::read_dir(data_dir)
+        .map_err(|e| format!("Failed to read dir {}: {}", data_dir.display(), e))?;
+    let mut version_dirs = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("Failed to read dir {}: {}", data_dir.display(), e))?
+            .path();
+
+        // The files at the root of the data directory hold the metadata
+        if path.is_dir() {
+            version_dirs.push(path);
+        }
+    }
+    version_dirs.sort();
+
+    for version_dir in version_dirs {
+        if !known_dirs.contains(&version_dir) {
+            problems.push(DataProblem::UnknownVersionDir {
+                path: version_dir.clone(),
+            });
+        }
+
+        let mut files = Vec::new();
+        list_files(&version_dir, &mut files)?;
+        files.sort();
+
+        problems.extend(
+            files
+                .into_iter()
+                .filter(|path| {
+                    !referenced_files.contains_key(path)
+                        && !schema_files.contains(path)
+                        && *path != version_dir.join(MANIFEST_FILENAME)
+                })
+                .map(|path| DataProblem::OrphanFile { path }),
+        );
+    }
+
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{hl_ciphertext, metadata_file, temp_dir, HL_CLIENT_KEY};
+
+    /// Writes a data directory with the high level api `testcases` and the empty `files`, given
+    /// relatively to the data directory
+    fn write_data_dir<S: AsRef<str>>(name: &str, testcases: &[S], files: &[&str]) -> PathBuf {
+        let data_dir = temp_dir(&format!("verify_{}", name));
+        fs::write(data_dir.join("shortint.ron"), metadata_file::<&str>(&[])).unwrap();
+        fs::write(
+            data_dir.join("high_level_api.ron"),
+            metadata_file(testcases),
+        )
+        .unwrap();
+
+        for file in files {
+            let path = data_dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        data_dir
+    }
+
+    #[cfg(feature = "generate")]
+    fn write_json_exports(data_dir: &Path) {
+        use crate::metadata::metadata_to_json;
+
+        for (module, filename) in METADATA_FILES {
+            let testcases: Vec<Testcase> = load_all_metadata(data_dir)
+                .unwrap()
+                .into_iter()
+                .filter(|testcase| testcase.tfhe_module == module)
+                .collect();
+            fs::write(
+                data_dir.join(json_metadata_filename(filename)),
+                metadata_to_json(&testcases),
+            )
+            .unwrap();
+        }
+        fs::write(
+            data_dir.join(METADATA_JSON_SCHEMA_FILE),
+            metadata_json_schema(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn consistent_data_dir_has_no_problem() {
+        let data_dir = write_data_dir(
+            "consistent",
+            &[HL_CLIENT_KEY],
+            &[
+                "0_6/manifest.ron",
+                "0_6/high_level_api/client_key.cbor",
+                "0_6/high_level_api/client_key.bcode",
+                "0_6/high_level_api/client_key.schema.ron",
+            ],
+        );
+        #[cfg(feature = "generate")]
+        write_json_exports(&data_dir);
+
+        let problems = verify_data_dir(&data_dir, &["0.6"]);
+        fs::remove_dir_all(&data_dir).unwrap();
+
+        assert_eq!(problems, Ok(Vec::new()));
+    }
+
+    #[test]
+    fn inconsistencies_are_reported() {
+        let ct = hl_ciphertext("0.6", "ct", "unknown_key");
+        let data_dir = write_data_dir(
+            "inconsistent",
+            &[&ct, &ct],
+            &[
+                "0_6/high_level_api/ct.cbor",
+                "0_6/high_level_api/old.cbor",
+                "0_5/high_level_api/ct.cbor",
+            ],
+        );
+
+        let problems = verify_data_dir(&data_dir, &["0.6"]).unwrap();
+        fs::remove_dir_all(&data_dir).unwrap();
+
+        assert!(problems.iter().any(DataProblem::is_error));
+
+        let module_dir = data_dir.join("0_6").join("high_level_api");
+        let id = TestcaseId {
+            version: "0.6".to_string(),
+            module: "high_level_api".to_string(),
+            test_filename: "ct".to_string(),
+        };
+        let expected = [
+            DataProblem::DuplicateTestcase {
+                id: id.clone(),
+                count: 2,
+            },
+            DataProblem::MissingFile {
+                path: module_dir.join("ct.bcode"),
+                referenced_by: vec![id.to_string()],
+            },
+            DataProblem::OrphanFile {
+                path: module_dir.join("old.cbor"),
+            },
+            DataProblem::UnknownVersionDir {
+                path: data_dir.join("0_5"),
+            },
+        ];
+        for problem in expected {
+            assert!(problems.contains(&problem), "{} is not reported", problem);
+        }
+        assert!(problems.iter().any(|problem| matches!(
+            problem,
+            DataProblem::InvalidReference { error } if error.contains("unknown_key")
+        )));
+        // The missing records are reported without being errors
+        let missing_snapshot = DataProblem::MissingSchemaSnapshot {
+            path: module_dir.join("ct.schema.ron"),
+            id: id.clone(),
+        };
+        let missing_tags = DataProblem::MissingVersionTags { id: id.clone() };
+        let missing_type = DataProblem::MissingStoredType { id: id.clone() };
+        for missing_record in [missing_snapshot, missing_tags, missing_type] {
+            assert!(
+                problems.contains(&missing_record),
+                "{} is not reported",
+                missing_record
+            );
+            assert!(!missing_record.is_error());
+        }
+        // The files of an unknown version do not belong to any testcase
+        assert!(problems.contains(&DataProblem::OrphanFile {
+            path: data_dir.join("0_5").join("high_level_api").join("ct.cbor")
+        }));
+    }
+}
@@ -0,0 +1,58 @@
+//! Checks that the JSON exports of the metadata are in sync with the ron metadata files
+#![cfg(feature = "generate")]
+
+use std::fs;
+
+use tfhe_backward_compat_data::{
+    data_dir,
+    metadata::{
+        check_json_metadata, json_metadata_filename, metadata_json_schema, metadata_to_json,
+        parse_json_metadata, parse_supported_metadata, METADATA_JSON_SCHEMA_FILE,
+    },
+    METADATA_FILES,
+};
+
+fn read_data_file(filename: &str) -> String {
+    let path = data_dir(env!("CARGO_MANIFEST_DIR")).join(filename);
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e))
+}
+
+#[test]
+fn json_metadata_describe_the_same_testcases() {
+    for (_, filename) in METADATA_FILES {
+        let testcases = parse_supported_metadata(&read_data_file(filename)).unwrap();
+        let json = read_data_file(&json_metadata_filename(filename));
+
+        if let Err(e) = check_json_metadata(&testcases, &json) {
+            panic!(
+                "{} is out of sync with {}, run the generator again:\n{}",
+                json_metadata_filename(filename),
+                filename,
+                e
+            );
+        }
+    }
+}
+
+#[test]
+fn json_metadata_round_trip() {
+    for (_, filename) in METADATA_FILES {
+        let testcases = parse_supported_metadata(&read_data_file(filename)).unwrap();
+        let exported = metadata_to_json(&testcases);
+
+        assert_eq!(
+            parse_json_metadata(&exported).unwrap().len(),
+            testcases.len()
+        );
+        check_json_metadata(&testcases, &exported).unwrap();
+    }
+}
+
+#[test]
+fn json_schema_is_up_to_date() {
+    assert!(
+        read_data_file(METADATA_JSON_SCHEMA_FILE) == metadata_json_schema(),
+        "{} does not match the metadata types, run the generator again",
+        METADATA_JSON_SCHEMA_FILE
+    );
+}